@@ -1,7 +1,19 @@
 // src/accounts.rs
 
-/// Listă statică de tokeni (simbol + mint address).
-/// Poți adăuga/șterge oricând tokeni aici.
+/// Mint-ul wrapped SOL (baza pentru calculele în lamports).
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// Programul SPL Token (conturile de token clasice ale wallet-ului).
+pub const SPL_TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+/// Programul Token-2022 (mints cu extensii; conturile lor nu apar sub SPL Token).
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+
+/// Programul Associated Token Account (ATA-urile wallet-ului).
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
+/// Tokeni cunoscuți implicit (simbol + mint address), punctul de pornire al TokenRegistry.
+/// Tokenii noi se adaugă în `tokens` din config.json (au prioritate față de lista asta).
 pub static TOKENS: &[(&str, &str)] = &[
     // SOL
     ("SOL", "So11111111111111111111111111111111111111112"),
@@ -16,11 +28,32 @@ pub static TOKENS: &[(&str, &str)] = &[
     // RAY
     ("RAY", "4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R"),
     // ORCA
-    ("ORCA", "orcaEKTdS75RoNiPu2vVucJmq9oQopZTcsfUytQU4o"),
+    ("ORCA", "orcaEKTdK7LKz57vaAYr9QeNsVEPfiu6QeMU1kektZE"),
     // SRM
     ("SRM", "SRMuApVNdxXokk5GT7XD5cUUgXMBCoAz2LHeuAoKWRt"),
     // JitoSOL
-    ("JitoSOL", "JitoSoL111111111111111111111111111111111111"),
+    ("JitoSOL", "J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn"),
     // stSOL
     ("stSOL", "7dHbWXmci3dT8UFYWYZweBLXgycu7Y3iL3jY1Xv6RBM6"),
-];
\ No newline at end of file
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+
+    #[test]
+    fn every_token_mint_is_a_valid_pubkey() {
+        for (sym, mint) in TOKENS {
+            assert!(Pubkey::from_str(mint).is_ok(), "{}: invalid mint {}", sym, mint);
+        }
+    }
+
+    #[test]
+    fn program_ids_are_valid_pubkeys() {
+        for id in [WSOL_MINT, SPL_TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID] {
+            assert!(Pubkey::from_str(id).is_ok(), "invalid pubkey {}", id);
+        }
+    }
+}
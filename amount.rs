@@ -0,0 +1,120 @@
+// src/amount.rs
+use anyhow::{anyhow, Result};
+use std::fmt;
+
+use crate::accounts::WSOL_MINT;
+
+pub const SOL_DECIMALS: u8 = 9;
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// SOL nativ, în lamports (fee-uri, tip-uri, praguri în SOL).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Lamports(pub u64);
+
+impl Lamports {
+    /// Conversie verificată: respinge NaN, valori negative și overflow.
+    pub fn from_sol(sol: f64) -> Result<Self> {
+        UiAmount(sol).to_raw(SOL_DECIMALS).map(Lamports)
+    }
+
+    pub fn as_sol(self) -> f64 {
+        self.0 as f64 / LAMPORTS_PER_SOL as f64
+    }
+
+    pub fn saturating_add(self, other: Lamports) -> Lamports {
+        Lamports(self.0.saturating_add(other.0))
+    }
+}
+
+impl fmt::Display for Lamports {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Cantitate „umană” (ex. 1.5 USDC); are sens doar împreună cu mint-ul/decimals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct UiAmount(pub f64);
+
+impl UiAmount {
+    pub fn to_raw(self, decimals: u8) -> Result<u64> {
+        let v = self.0;
+        if !v.is_finite() || v < 0.0 {
+            return Err(anyhow!("invalid amount {}", v));
+        }
+        let raw = (v * 10f64.powi(decimals as i32)).round();
+        if raw > u64::MAX as f64 {
+            return Err(anyhow!("amount {} overflows u64 at {} decimals", v, decimals));
+        }
+        Ok(raw as u64)
+    }
+}
+
+impl fmt::Display for UiAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Unități raw ale unui mint SPL (sau lamports pentru wSOL), cu decimals-urile lui.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawAmount {
+    pub amount: u64,
+    pub mint: String,
+    pub decimals: u8,
+}
+
+impl RawAmount {
+    pub fn new(amount: u64, mint: &str, decimals: u8) -> Self {
+        Self { amount, mint: mint.to_string(), decimals }
+    }
+
+    pub fn from_ui(ui: UiAmount, mint: &str, decimals: u8) -> Result<Self> {
+        Ok(Self::new(ui.to_raw(decimals)?, mint, decimals))
+    }
+
+    pub fn lamports(l: Lamports) -> Self {
+        Self::new(l.0, WSOL_MINT, SOL_DECIMALS)
+    }
+
+    pub fn is_sol(&self) -> bool {
+        self.mint == WSOL_MINT
+    }
+
+    pub fn ui(&self) -> UiAmount {
+        UiAmount(self.amount as f64 / 10f64.powi(self.decimals as i32))
+    }
+
+    /// o fracțiune (0..=1) din cantitate, rotunjită în jos
+    pub fn fraction(&self, f: f64) -> Self {
+        let f = f.clamp(0.0, 1.0);
+        Self::new((self.amount as f64 * f) as u64, &self.mint, self.decimals)
+    }
+}
+
+impl fmt::Display for RawAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.amount, self.ui())
+    }
+}
+
+/// Cursul unui mint față de SOL, ca pragurile/fee-urile în lamports să se poată compara
+/// cu PnL-ul unui ciclu care începe în alt token.
+#[derive(Debug, Clone)]
+pub struct SolRate {
+    pub mint: String,
+    pub decimals: u8,
+    /// unități raw din `mint` pentru un lamport
+    pub raw_per_lamport: f64,
+}
+
+impl SolRate {
+    pub fn sol() -> Self {
+        Self { mint: WSOL_MINT.to_string(), decimals: SOL_DECIMALS, raw_per_lamport: 1.0 }
+    }
+
+    pub fn to_raw(&self, l: Lamports) -> RawAmount {
+        let amount = (l.0 as f64 * self.raw_per_lamport).round().min(u64::MAX as f64) as u64;
+        RawAmount::new(amount, &self.mint, self.decimals)
+    }
+}
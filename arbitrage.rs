@@ -1,42 +1,147 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
-use crate::resolver::Resolver;
+use crate::accounts::WSOL_MINT;
+use crate::amount::UiAmount;
+use crate::jupiter::{JupiterClient, QuoteResponse};
+use crate::limiter::Limiter;
+use crate::registry::TokenRegistry;
 
-pub struct Arbitrage;
+/// Un ciclu A -> B -> C -> A evaluat cu quote-uri reale.
+#[derive(Debug, Clone)]
+pub struct RouteOpportunity {
+    pub symbols: [String; 3],
+    /// mints A, B, C
+    pub mints: [String; 3],
+    /// suma inițială, raw în A
+    pub amount_in: u64,
+    /// ce se întoarce în A după C->A
+    pub back_a: u64,
+    /// quote-urile A->B, B->C, C->A (gata de dat la /swap)
+    pub quotes: [QuoteResponse; 3],
+    /// semnături + priority fee + tip + buffer
+    pub costs_lamports: u64,
+    /// back_a - amount_in - costs (A e mereu SOL, deci lamports)
+    pub net_profit: i128,
+}
+
+impl RouteOpportunity {
+    pub fn label(&self) -> String {
+        format!("{} -> {} -> {} -> {}", self.symbols[0], self.symbols[1], self.symbols[2], self.symbols[0])
+    }
+
+    pub fn net_profit_bps(&self) -> f64 {
+        if self.amount_in == 0 {
+            return 0.0;
+        }
+        self.net_profit as f64 * 10_000.0 / self.amount_in as f64
+    }
+
+    pub fn max_impact_bps(&self) -> f64 {
+        self.quotes.iter().map(|q| q.price_impact_bps()).fold(0.0, f64::max)
+    }
+}
+
+pub struct Arbitrage {
+    /// costuri fixe per ciclu (3 semnături, priority fee, tip Jito, buffer)
+    costs_lamports: u64,
+}
 
 impl Arbitrage {
-    pub fn new() -> Self {
-        Self
+    pub fn new(costs_lamports: u64) -> Self {
+        Self { costs_lamports }
     }
 
-    /// Scanare triunghiulară minimală: doar generează rutele și pune profit estimat = 0.0.
-    /// O facem să compileze și să putem avansa; ulterior conectăm quotes reale din Resolver.
+    /// Scanare triunghiulară: primul simbol e baza (A, trebuie să fie SOL ca să putem scădea
+    /// costurile în lamports), restul se combină în perechi ordonate (B, C).
+    /// Pentru fiecare ciclu se cer 3 quote-uri înlănțuite; rezultatele vin sortate după profit net.
     pub async fn check_all_routes(
         &self,
-        _resolver: &Resolver,
-        tokens: &Vec<&'static str>,
-        amount: u64,
-        slippage_bps: u64,
-    ) -> Result<Vec<(String, f64)>> {
+        jup: &JupiterClient,
+        limiter: &Limiter,
+        registry: &TokenRegistry,
+        tokens: &[&str],
+        amount_ui: f64,
+    ) -> Result<Vec<RouteOpportunity>> {
+        let Some((base_sym, rest)) = tokens.split_first() else {
+            return Err(anyhow!("tri scan: lista de tokeni e goală"));
+        };
+        let base = registry.resolve(base_sym)?;
+        if base.mint != WSOL_MINT {
+            return Err(anyhow!("tri scan: baza trebuie să fie SOL, nu {} ({})", base_sym, base.mint));
+        }
+        let base_decimals = base.decimals.unwrap_or(9);
+        let amount_in = UiAmount(amount_ui).to_raw(base_decimals)?;
+        if amount_in == 0 {
+            return Err(anyhow!("tri scan: amount {} rotunjit la 0", amount_ui));
+        }
+
         let mut out = Vec::new();
 
-        // itertools::Itertools::permutations(3) -> Iterator<Item = Vec<&str>>
-        for perm in tokens.iter().copied().permutations(3) {
-            let a = perm[0];
-            let b = perm[1];
-            let c = perm[2];
+        for perm in rest.iter().copied().permutations(2) {
+            let (b_sym, c_sym) = (perm[0], perm[1]);
+            let (b, c) = match (registry.resolve(b_sym), registry.resolve(c_sym)) {
+                (Ok(b), Ok(c)) => (b, c),
+                (Err(e), _) | (_, Err(e)) => {
+                    eprintln!("[TRI] skip {}->{}->{}: {}", base_sym, b_sym, c_sym, e);
+                    continue;
+                }
+            };
 
-            // TODO: aici vom chema resolver-ul pentru 3 quote-uri (A->B, B->C, C->A)
-            // și calculăm profitul real. Temporar punem 0.0 ca să treacă build-ul.
-            let est_profit = 0.0_f64;
+            let quotes = match self
+                .quote_cycle(jup, limiter, [&base.mint, &b.mint, &c.mint], amount_in)
+                .await
+            {
+                Ok(q) => q,
+                Err(e) => {
+                    eprintln!("[TRI] quote error {}->{}->{}: {:#}", base_sym, b_sym, c_sym, e);
+                    continue;
+                }
+            };
 
-            out.push((
-                format!("{} -> {} -> {} (amt={}, slip={}bps)", a, b, c, amount, slippage_bps),
-                est_profit,
-            ));
+            let back_a = quotes[2].out_amount;
+            let net_profit = back_a as i128 - amount_in as i128 - self.costs_lamports as i128;
+
+            out.push(RouteOpportunity {
+                symbols: [base.symbol.clone(), b.symbol.clone(), c.symbol.clone()],
+                mints: [base.mint.clone(), b.mint.clone(), c.mint.clone()],
+                amount_in,
+                back_a,
+                quotes,
+                costs_lamports: self.costs_lamports,
+                net_profit,
+            });
         }
 
+        out.sort_by_key(|x| std::cmp::Reverse(x.net_profit));
         Ok(out)
     }
-}
\ No newline at end of file
+
+    /// A->B, B->C, C->A, fiecare pe outAmount-ul precedent.
+    async fn quote_cycle(
+        &self,
+        jup: &JupiterClient,
+        limiter: &Limiter,
+        mints: [&str; 3],
+        amount_in: u64,
+    ) -> Result<[QuoteResponse; 3]> {
+        let [a, b, c] = mints;
+
+        limiter.wait().await;
+        let q1 = jup.quote(a, b, amount_in, Some(false)).await?;
+        if q1.out_amount == 0 {
+            return Err(anyhow!("A->B out=0"));
+        }
+
+        limiter.wait().await;
+        let q2 = jup.quote(b, c, q1.out_amount, Some(false)).await?;
+        if q2.out_amount == 0 {
+            return Err(anyhow!("B->C out=0"));
+        }
+
+        limiter.wait().await;
+        let q3 = jup.quote(c, a, q2.out_amount, Some(false)).await?;
+
+        Ok([q1, q2, q3])
+    }
+}
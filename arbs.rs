@@ -1,19 +1,30 @@
 // src/arbs.rs
 use std::{sync::Arc, time::Duration};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use base64::prelude::*;
-use solana_client::rpc_client::RpcClient;
-use solana_client::rpc_config::RpcSendTransactionConfig;
 use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
 use solana_sdk::message::VersionedMessage;
-use solana_sdk::native_token::sol_to_lamports;
-use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
 use solana_sdk::transaction::VersionedTransaction;
 
-use crate::config::{Config, JitoConfig};
-use crate::jupiter::JupiterClient;
+use crate::accounts::WSOL_MINT;
+use crate::amount::{Lamports, RawAmount, SolRate, UiAmount, SOL_DECIMALS};
+use crate::arbitrage::{Arbitrage, RouteOpportunity};
+use crate::config::{Config, SizingConfig};
+use crate::confirm;
+use crate::events::{self, Event};
+use crate::jito::JitoClient;
+use crate::jupiter::{JupiterClient, QuoteResponse};
+use crate::limiter::Limiter;
+use crate::pnl::{PnlLedger, TradeCtx};
+use crate::registry::{self, TokenRegistry};
+use crate::risk::{self, Reject, TradePath};
 use crate::rpc::RpcRotator;
+use crate::simulate;
+use crate::sizing::{self, CycleEval};
 use colored::Colorize;
 /// rulează o singură trecere peste bundles definite în config
 pub async fn run_bundles_once(
@@ -21,7 +32,10 @@ pub async fn run_bundles_once(
     jup: &JupiterClient,
     rpcs: &Arc<RpcRotator>,
     kp: &Arc<Keypair>,
-    jito_cfg: Option<&JitoConfig>,
+    jito: Option<&JitoClient>,
+    ledger: &Arc<PnlLedger>,
+    limiter: &Limiter,
+    registry: Option<&TokenRegistry>,
  ) {
     // dacă nu există secțiunea bundles în config, ieșim
     if cfg.bundles.is_none() {
@@ -29,24 +43,25 @@ pub async fn run_bundles_once(
     }
     let b = cfg.bundles.as_ref().unwrap();
 
-    if let Some(j) = jito_cfg {
-        println!(
-            "[BUNDLES] Jito ON (tip_acct={}, default_tip={})",
-            j.tip_account, j.default_tip_lamports
+    if let Some(j) = jito {
+        diag!(
+            "[BUNDLES] Jito ON (tip_acct={}, default_tip={}, retries={})",
+            j.tip_account, j.default_tip_lamports, j.max_bundle_retries
         );
     } else {
-        println!("[BUNDLES] Jito OFF");
+        diag!("[BUNDLES] Jito OFF");
     }
     
     // arbs.rs, în run_bundles_once, după let b = cfg.bundles.as_ref().unwrap();
     let exec = &b.execution;
-     println!(
-     "[BUNDLES EXEC] simulate_first={} commit={} timeout_ms={} retries={} impact_bps_limit={} fee_buf={} min_cycle_pnl_lamports={}",
+     diag!(
+     "[BUNDLES EXEC] simulate_first={} commit={} timeout_ms={} retries={} impact_bps_limit(2L/3L)={}/{} fee_buf={} min_cycle_pnl_lamports={}",
     exec.simulate_first,
     exec.commit,
     exec.timeout_ms,
     exec.retries,
-    exec.price_impact_bps_limit,
+    risk::max_impact_bps(cfg, TradePath::TwoLeg, None),
+    risk::max_impact_bps(cfg, TradePath::ThreeLeg, None),
     exec.fee_buffer_lamports,
     exec.min_cycle_pnl_lamports
     );
@@ -57,8 +72,9 @@ pub async fn run_bundles_once(
     let from  = bl.from.as_str();
     let to    = bl.to.as_str();
     let label = bl.label.as_deref();
+    let max_impact_bps = risk::max_impact_bps(cfg, TradePath::TwoLeg, bl.max_price_impact_bps);
 
-    if let Err(e) = try_two_leg(cfg, jup, rpcs, kp, jito_cfg, from, to, label).await {
+    if let Err(e) = try_two_leg(cfg, jup, rpcs, kp, jito, ledger, limiter, from, to, label, max_impact_bps).await {
         eprintln!("[BUNDLE 2L] ERR {}: {:?}", label.unwrap_or("?"), e);
     }
     tokio::time::sleep(Duration::from_millis(50)).await;
@@ -67,8 +83,9 @@ pub async fn run_bundles_once(
     // TRI-LEG
     for bl3 in &b.tri_leg {
         let label = bl3.label.as_deref();
+        let max_impact_bps = risk::max_impact_bps(cfg, TradePath::ThreeLeg, bl3.max_price_impact_bps);
         if let Err(e) =
-            try_three_leg(cfg, jup, rpcs, kp, jito_cfg, &bl3.legs, label).await
+            try_three_leg(cfg, jup, rpcs, kp, jito, ledger, limiter, &bl3.legs, label, max_impact_bps).await
         {
             eprintln!(
                 "[BUNDLE 3L] ERR {}: {:?}",
@@ -78,6 +95,158 @@ pub async fn run_bundles_once(
         }
         tokio::time::sleep(Duration::from_millis(50)).await;
     }
+
+    // TRI-SCAN (rute dinamice din simboluri)
+    if let (Some(ts), Some(registry)) = (b.tri_scan.as_ref(), registry) {
+        if ts.enabled {
+            let tokens: Vec<&str> = ts.tokens.iter().map(|t| t.as_str()).collect();
+            let arb = Arbitrage::new(cycle_costs_lamports(cfg, jito, 3));
+            match arb.check_all_routes(jup, limiter, registry, &tokens, cfg.notional_sol).await {
+                Ok(opps) => {
+                    for opp in opps.iter().take(ts.max_execs_per_pass) {
+                        if let Err(e) = execute_route(cfg, jup, rpcs, kp, jito, ledger, limiter, opp).await {
+                            eprintln!("[TRI-SCAN] ERR {}: {:?}", opp.label(), e);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("[TRI-SCAN] scan error: {:#}", e),
+            }
+        }
+    }
+}
+
+/// Costuri fixe estimate pentru un ciclu cu `legs` tranzacții de swap:
+/// buffer + semnături + priority fee + tip Jito (dacă trimitem prin Jito).
+pub fn cycle_costs_lamports(cfg: &Config, jito: Option<&JitoClient>, legs: u64) -> u64 {
+    let fee_buffer = cfg.bundles.as_ref().map(|b| b.execution.fee_buffer_lamports).unwrap_or(0);
+    let tip = jito.map(|j| j.default_tip_lamports).unwrap_or(0);
+    fee_buffer + legs * cfg.fees.lamports_per_signature + cfg.fees.priority_fee_lamports + tip
+}
+
+/// `bundles.sizing`, doar dacă e activată.
+fn sizing_cfg(cfg: &Config) -> Option<&SizingConfig> {
+    cfg.bundles.as_ref()?.sizing.as_ref().filter(|s| s.enabled)
+}
+
+/// Suma de intrare în `mint`: `override_ui` (unități UI ale lui `mint`) dacă e dat, altfel
+/// `notional_sol` la cursul unui quote WSOL→mint (aceeași valoare, nu același număr de unități
+/// raw). Cursul se folosește și pentru pragurile/fee-urile în lamports.
+pub async fn notional_in(
+    cfg: &Config,
+    jup: &JupiterClient,
+    rpcs: &RpcRotator,
+    limiter: &Limiter,
+    mint: &str,
+    override_ui: Option<f64>,
+) -> Result<(RawAmount, SolRate)> {
+    let lamports = Lamports::from_sol(cfg.notional_sol)?;
+    if mint == WSOL_MINT {
+        let amount = match override_ui {
+            Some(ui) => RawAmount::from_ui(UiAmount(ui), mint, SOL_DECIMALS)?,
+            None => RawAmount::lamports(lamports),
+        };
+        return Ok((amount, SolRate::sol()));
+    }
+
+    let decimals = registry::global().decimals(rpcs, mint).await?;
+    limiter.wait().await;
+    let q = jup.quote(WSOL_MINT, mint, lamports.0, None).await?;
+    if q.out_amount == 0 || lamports.0 == 0 {
+        return Err(anyhow!("no SOL price for {}", mint));
+    }
+    let rate = SolRate {
+        mint: mint.to_string(),
+        decimals,
+        raw_per_lamport: q.out_amount as f64 / lamports.0 as f64,
+    };
+    let amount = match override_ui {
+        Some(ui) => RawAmount::from_ui(UiAmount(ui), mint, decimals)?,
+        None => RawAmount::new(q.out_amount, mint, decimals),
+    };
+    Ok((amount, rate))
+}
+
+/// Execută o oportunitate din `Arbitrage::check_all_routes` cu quote-urile ei, după aceleași
+/// praguri ca bundle-urile pe 3 picioare din config.
+pub async fn execute_route(
+    cfg: &Config,
+    jup: &JupiterClient,
+    rpcs: &Arc<RpcRotator>,
+    kp: &Arc<Keypair>,
+    jito: Option<&JitoClient>,
+    ledger: &Arc<PnlLedger>,
+    limiter: &Limiter,
+    opp: &RouteOpportunity,
+) -> Result<()> {
+    let Some(b) = cfg.bundles.as_ref() else {
+        return Ok(());
+    };
+    let exec = &b.execution;
+    let label = opp.label();
+    let max_impact_bps = risk::max_impact_bps(cfg, TradePath::ThreeLeg, None);
+
+    diag!(
+        "[TRI-SCAN] {} in={} back={} costs={} pnl={} ({:.2}bp) max_impact={:.2}bp",
+        label, opp.amount_in, opp.back_a, opp.costs_lamports, opp.net_profit,
+        opp.net_profit_bps(), opp.max_impact_bps()
+    );
+
+    // ruta a fost găsită la notional fix; re-cotăm în jurul lui pentru mărimea optimă
+    let sized = match sizing_cfg(cfg) {
+        Some(sz) => {
+            let [a, b2, c] = &opp.mints;
+            let path = [a.as_str(), b2.as_str(), c.as_str(), a.as_str()];
+            sizing::optimize(jup, limiter, sz, &path, opp.amount_in, opp.costs_lamports, max_impact_bps).await
+        }
+        None => None,
+    };
+    let (quotes, net) = match sized {
+        Some(best) if best.net > opp.net_profit => {
+            diag!("[TRI-SCAN SIZE] {} in={} pnl={} (fix: {})", label, best.amount_in, best.net, opp.net_profit);
+            let net = best.net;
+            (cycle_quotes::<3>(best)?, net)
+        }
+        _ => (opp.quotes.clone(), opp.net_profit),
+    };
+    let thresh = exec.min_cycle_pnl_lamports as i128;
+    events::emit(Event::CycleEvaluated {
+        path: TradePath::ThreeLeg.as_str(),
+        key: label.clone(),
+        amount_in: quotes[0].in_amount,
+        back: quotes[2].out_amount,
+        costs: opp.costs_lamports,
+        pnl: net,
+        thresh,
+    });
+    let profit = if net < thresh { Err(Reject::Profit { pnl: net, thresh }) } else { Ok(()) };
+    let verdict = quotes
+        .iter()
+        .enumerate()
+        .try_for_each(|(i, q)| risk::check_leg(q, i + 1, max_impact_bps))
+        .and(profit);
+    if let Err(r) = verdict {
+        diag!("{} {}", "[TRI-SCAN DECISION] NO-EXEC".red().bold(), r);
+        risk::record(TradePath::ThreeLeg, &label, &r);
+        return Ok(());
+    }
+
+    let ctx = TradeCtx {
+        key: label,
+        base_mint: opp.mints[0].clone(),
+        estimated: net,
+        tip_lamports: jito.map(|j| j.default_tip_lamports).unwrap_or(0),
+        sol_rate: None,
+        mark_to: None,
+    };
+    let [q1, q2, q3] = &quotes;
+    execute_three_leg(cfg, jup, rpcs, kp, jito, ledger, [q1, q2, q3], ctx).await
+}
+
+/// Quote-urile celei mai bune mărimi, câte unul per picior.
+fn cycle_quotes<const N: usize>(best: CycleEval) -> Result<[QuoteResponse; N]> {
+    best.quotes
+        .try_into()
+        .map_err(|q: Vec<QuoteResponse>| anyhow!("sizing: expected {} quotes, got {}", N, q.len()))
 }
 
 async fn try_two_leg(
@@ -85,83 +254,158 @@ async fn try_two_leg(
     jup: &JupiterClient,
     rpcs: &Arc<RpcRotator>,
     kp: &Arc<Keypair>,
-    _jito_cfg: Option<&JitoConfig>,
+    jito: Option<&JitoClient>,
+    ledger: &Arc<PnlLedger>,
+    limiter: &Limiter,
     a_mint: &str,
     b_mint: &str,
     label: Option<&str>,
+    max_impact_bps: f64,
 ) -> Result<()> {
     let exec = &cfg.bundles.as_ref().unwrap().execution;
-    let in_u: u64 = sol_to_lamports(cfg.notional_sol);
-    let client: RpcClient = rpcs.client();
+    let (notional, rate) = notional_in(cfg, jup, rpcs, limiter, a_mint, None).await?;
+    let in_u: u64 = notional.amount;
+
+    // costurile și pragul sunt în lamports -> în unitățile lui A
+    let fee_buf = rate.to_raw(Lamports(cycle_costs_lamports(cfg, jito, 2))).amount;
+    let thresh = rate.to_raw(Lamports(exec.min_cycle_pnl_lamports)).amount;
+
+    let key = label.map(str::to_string).unwrap_or_else(|| format!("{}→{}", a_mint, b_mint));
+    let evaluated = |amount_in: u64, back: u64, pnl: i128| {
+        events::emit(Event::CycleEvaluated {
+            path: TradePath::TwoLeg.as_str(),
+            key: key.clone(),
+            amount_in,
+            back,
+            costs: fee_buf,
+            pnl,
+            thresh: thresh as i128,
+        })
+    };
+    let ctx = |pnl: i128| TradeCtx {
+        key: key.clone(),
+        base_mint: a_mint.to_string(),
+        estimated: pnl,
+        tip_lamports: jito.map(|j| j.default_tip_lamports).unwrap_or(0),
+        sol_rate: Some(rate.clone()),
+        mark_to: None,
+    };
+
+    if let Some(sz) = sizing_cfg(cfg) {
+        let path = [a_mint, b_mint, a_mint];
+        let Some(best) = sizing::optimize(jup, limiter, sz, &path, in_u, fee_buf, max_impact_bps).await else {
+            diag!("[B2L DECISION] NO-EXEC: {}", Reject::NoSize);
+            risk::record(TradePath::TwoLeg, &key, &Reject::NoSize);
+            return Ok(());
+        };
+        diag!(
+            "[B2L SIZE] {} {}↔{} in={} (notional {}) fee_buf={} pnl={} thresh={}",
+            label.unwrap_or(""),
+            a_mint, b_mint, best.amount_in, in_u, fee_buf, best.net, thresh
+        );
+        evaluated(best.amount_in, best.quotes.last().map(|q| q.out_amount).unwrap_or(0), best.net);
+        if best.net < thresh as i128 {
+            let r = Reject::Profit { pnl: best.net, thresh: thresh as i128 };
+            diag!("{}", format!("[B2L DECISION] NO-EXEC: {}", r).red().bold());
+            risk::record(TradePath::TwoLeg, &key, &r);
+            return Ok(());
+        }
+        let pnl = best.net;
+        let [q_fwd, q_rev] = cycle_quotes::<2>(best)?;
+        return execute_two_leg(cfg, jup, rpcs, kp, jito, ledger, [&q_fwd, &q_rev], ctx(pnl)).await;
+    }
 
     // Quote A->B
     let q_fwd = jup.quote(a_mint, b_mint, in_u, Some(false)).await?;
-    let out_b = parse_out(&q_fwd);
-    let impact_fwd = parse_impact(&q_fwd);
-    let amm_fwd = parse_amm(&q_fwd);
+    let out_b = q_fwd.out_amount;
+    let impact_fwd = q_fwd.price_impact_pct;
+    let amm_fwd = q_fwd.amm_label();
 
-    println!(
+    diag!(
         "[B2L FWD] {} {}→{} out(B)={} amm={} impact={}",
         label.unwrap_or(""),
         a_mint, b_mint, out_b, amm_fwd, impact_fwd
     );
 
-    if out_b == 0 || bps(impact_fwd) > exec.price_impact_bps_limit as f64 {
-        println!("[B2L DECISION] NO-EXEC: impact prea mare sau out=0");
+    if let Err(r) = risk::check_leg(&q_fwd, 1, max_impact_bps) {
+        diag!("[B2L DECISION] NO-EXEC: {}", r);
+        risk::record(TradePath::TwoLeg, &key, &r);
         return Ok(());
     }
 
     // Quote B->A
     let q_rev = jup.quote(b_mint, a_mint, out_b, Some(false)).await?;
-    let back_a = parse_out(&q_rev);
-    let impact_rev = parse_impact(&q_rev);
-    let amm_rev = parse_amm(&q_rev);
-
-    let fee_buf = exec.fee_buffer_lamports
-        + 2 * cfg.fees.lamports_per_signature
-        + cfg.fees.priority_fee_lamports;
+    let back_a = q_rev.out_amount;
+    let impact_rev = q_rev.price_impact_pct;
+    let amm_rev = q_rev.amm_label();
 
     let pnl: i128 = back_a as i128 - in_u as i128 - fee_buf as i128;
 
-    println!(
+    diag!(
         "[B2L REV] {} {}→{} back(A)={} amm={} impact={} | fee_buf={} pnl={} thresh={}",
         label.unwrap_or(""),
-        b_mint, a_mint, back_a, amm_rev, impact_rev, fee_buf, pnl, exec.min_cycle_pnl_lamports
+        b_mint, a_mint, back_a, amm_rev, impact_rev, fee_buf, pnl, thresh
     );
+    evaluated(in_u, back_a, pnl);
 
-    if bps(impact_rev) > exec.price_impact_bps_limit as f64 {
-        println!("{}", "[B2L DECISION] NO-EXEC: impact prea mare".red().bold());
-
+    if let Err(r) = risk::check_leg(&q_rev, 2, max_impact_bps) {
+        diag!("{}", format!("[B2L DECISION] NO-EXEC: {}", r).red().bold());
+        risk::record(TradePath::TwoLeg, &key, &r);
         return Ok(());
     }
-    if pnl < exec.min_cycle_pnl_lamports as i128 {
-        println!("{}", format!("[B2L DECISION] NO-EXEC: pnl {} < {}", pnl, exec.min_cycle_pnl_lamports).red().bold());
+    if pnl < thresh as i128 {
+        let r = Reject::Profit { pnl, thresh: thresh as i128 };
+        diag!("{}", format!("[B2L DECISION] NO-EXEC: {}", r).red().bold());
+        risk::record(TradePath::TwoLeg, &key, &r);
         return Ok(());
     }
 
-    if exec.simulate_first {
-        // folosim quote-ul fwd să construim tx (Jupiter ne dă direct tx b64)
-        let tx_b64 = jup.swap_tx(&q_fwd, &kp.pubkey().to_string(), cfg.fees.priority_fee_lamports).await?;
-        // doar asigură-te că se poate deserializa (simulare superficială)
-        let _vtx: VersionedTransaction = bincode::deserialize(&BASE64_STANDARD.decode(&tx_b64)?)?;
-        println!("[B2L] simulate_first OK");
+    execute_two_leg(cfg, jup, rpcs, kp, jito, ledger, [&q_fwd, &q_rev], ctx(pnl)).await
+}
+
+/// Ambele picioare din quote-urile deja luate; se trimit împreună, atomic.
+async fn execute_two_leg(
+    cfg: &Config,
+    jup: &JupiterClient,
+    rpcs: &Arc<RpcRotator>,
+    kp: &Arc<Keypair>,
+    jito: Option<&JitoClient>,
+    ledger: &Arc<PnlLedger>,
+    quotes: [&QuoteResponse; 2],
+    ctx: TradeCtx,
+) -> Result<()> {
+    let exec = &cfg.bundles.as_ref().unwrap().execution;
+    let [q_fwd, q_rev] = quotes;
+    let user = kp.pubkey().to_string();
+    let mut legs = decode_legs(&[
+        jup.swap_tx(q_fwd, &user, cfg.fees.priority_fee_lamports).await?,
+        jup.swap_tx(q_rev, &user, cfg.fees.priority_fee_lamports).await?,
+    ])?;
+
+    if exec.simulate_first && !simulate_legs(rpcs, kp, &mut legs, cfg.fees.priority_fee_lamports, "B2L").await? {
+        return Ok(());
     }
 
     if !exec.commit || cfg.dry_run {
-        println!("{}", "[B2L] DRY (commit=false sau cfg.dry_run=true) — NU trimit tx".yellow());
+        diag!("{}", "[B2L] DRY (commit=false sau cfg.dry_run=true) — NU trimit tx".yellow());
         return Ok(());
     }
 
-    // Trimite efectiv A->B (single leg). B->A îl va închide bucla clasică când e profitabil.
-    // (pentru bundle în același slot, se va folosi ulterior JitoClient)
-    let tx_b64 = jup.swap_tx(&q_fwd, &kp.pubkey().to_string(), cfg.fees.priority_fee_lamports).await?;
-    let sig = send_signed(&client, kp, &tx_b64)?;
-    println!(
-    "{} {} sig={}",
-    "[B2L EXECUTED]".green().bold(),
-    label.unwrap_or(""),
-    sig
+    // Fără Jito nu avem garanția all-or-nothing; nu lăsăm poziția deschisă pe B.
+    let Some(jito) = jito else {
+        diag!("{}", "[B2L] Jito OFF — ciclul nu poate fi trimis atomic, skip".yellow());
+        return Ok(());
+    };
+
+    let bundle_id = send_bundle_atomic(rpcs, jito, kp, &legs).await?;
+    diag!(
+    "{} {} bundle={}",
+    "[B2L SENT]".green(),
+    ctx.key,
+    bundle_id.id
     );
+    events::emit(Event::TxSent { path: TradePath::TwoLeg.as_str(), key: ctx.key.clone(), sig: bundle_id.first_sig.to_string() });
+    spawn_bundle_tracker(rpcs.clone(), ledger.clone(), kp.pubkey(), "B2L", ctx, bundle_id);
 
     Ok(())
 }
@@ -171,111 +415,211 @@ async fn try_three_leg(
     jup: &JupiterClient,
     rpcs: &Arc<RpcRotator>,
     kp: &Arc<Keypair>,
-    _jito_cfg: Option<&JitoConfig>,
+    jito: Option<&JitoClient>,
+    ledger: &Arc<PnlLedger>,
+    limiter: &Limiter,
     path: &Vec<String>,
     label: Option<&str>,
+    max_impact_bps: f64,
 ) -> Result<()> {
     if path.len() != 4 {
-        println!("[B3L] path invalid (trebuie 4 mints A→B→C→A)");
+        diag!("[B3L] path invalid (trebuie 4 mints A→B→C→A)");
         return Ok(());
     }
     let exec = &cfg.bundles.as_ref().unwrap().execution;
     let a = &path[0]; let b = &path[1]; let c = &path[2]; let a2 = &path[3];
     if a != a2 {
-        println!("[B3L] path trebuie să se închidă în A");
+        diag!("[B3L] path trebuie să se închidă în A");
         return Ok(());
     }
-    let client: RpcClient = rpcs.client();
-    let amt_a: u64 = sol_to_lamports(cfg.notional_sol);
+    let (notional, rate) = notional_in(cfg, jup, rpcs, limiter, a, None).await?;
+    let amt_a: u64 = notional.amount;
+
+    let fee_buf = rate.to_raw(Lamports(cycle_costs_lamports(cfg, jito, 3))).amount;
+    let thresh = rate.to_raw(Lamports(exec.min_cycle_pnl_lamports)).amount;
+
+    let key = label.map(str::to_string).unwrap_or_else(|| format!("{}→{}→{}", a, b, c));
+    let evaluated = |amount_in: u64, back: u64, pnl: i128| {
+        events::emit(Event::CycleEvaluated {
+            path: TradePath::ThreeLeg.as_str(),
+            key: key.clone(),
+            amount_in,
+            back,
+            costs: fee_buf,
+            pnl,
+            thresh: thresh as i128,
+        })
+    };
+    let ctx = |pnl: i128| TradeCtx {
+        key: key.clone(),
+        base_mint: a.clone(),
+        estimated: pnl,
+        tip_lamports: jito.map(|j| j.default_tip_lamports).unwrap_or(0),
+        sol_rate: Some(rate.clone()),
+        mark_to: None,
+    };
+
+    if let Some(sz) = sizing_cfg(cfg) {
+        let mints = [a.as_str(), b.as_str(), c.as_str(), a.as_str()];
+        let Some(best) = sizing::optimize(jup, limiter, sz, &mints, amt_a, fee_buf, max_impact_bps).await else {
+            diag!("[B3L] NO-EXEC: {}", Reject::NoSize);
+            risk::record(TradePath::ThreeLeg, &key, &Reject::NoSize);
+            return Ok(());
+        };
+        diag!(
+            "[B3L SIZE] {} A={} B={} C={} | in={} (notional {}) fee_buf={} pnl={} thresh={}",
+            label.unwrap_or(""),
+            a, b, c, best.amount_in, amt_a, fee_buf, best.net, thresh
+        );
+        evaluated(best.amount_in, best.quotes.last().map(|q| q.out_amount).unwrap_or(0), best.net);
+        if best.net < thresh as i128 {
+            let r = Reject::Profit { pnl: best.net, thresh: thresh as i128 };
+            diag!("{} {}", "[B3L DECISION] NO-EXEC".red().bold(), r);
+            risk::record(TradePath::ThreeLeg, &key, &r);
+            return Ok(());
+        }
+        let pnl = best.net;
+        let [q1, q2, q3] = cycle_quotes::<3>(best)?;
+        return execute_three_leg(cfg, jup, rpcs, kp, jito, ledger, [&q1, &q2, &q3], ctx(pnl)).await;
+    }
 
     // A->B
     let q1 = jup.quote(a, b, amt_a, Some(false)).await?;
-    let out_b = parse_out(&q1);
-    let imp1 = parse_impact(&q1);
-    if out_b == 0 || bps(imp1) > exec.price_impact_bps_limit as f64 {
-        println!("[B3L] stop după A->B: impact/out invalid");
+    let out_b = q1.out_amount;
+    let imp1 = q1.price_impact_pct;
+    if let Err(r) = risk::check_leg(&q1, 1, max_impact_bps) {
+        diag!("[B3L] stop după A->B: {}", r);
+        risk::record(TradePath::ThreeLeg, &key, &r);
         return Ok(());
     }
 
     // B->C
     let q2 = jup.quote(b, c, out_b, Some(false)).await?;
-    let out_c = parse_out(&q2);
-    let imp2 = parse_impact(&q2);
-    if out_c == 0 || bps(imp2) > exec.price_impact_bps_limit as f64 {
-        println!("[B3L] stop după B->C: impact/out invalid");
+    let out_c = q2.out_amount;
+    let imp2 = q2.price_impact_pct;
+    if let Err(r) = risk::check_leg(&q2, 2, max_impact_bps) {
+        diag!("[B3L] stop după B->C: {}", r);
+        risk::record(TradePath::ThreeLeg, &key, &r);
         return Ok(());
     }
 
     // C->A
     let q3 = jup.quote(c, a, out_c, Some(false)).await?;
-    let back_a = parse_out(&q3);
-    let imp3 = parse_impact(&q3);
-
-    let fee_buf = exec.fee_buffer_lamports
-        + 3 * cfg.fees.lamports_per_signature
-        + cfg.fees.priority_fee_lamports;
+    let back_a = q3.out_amount;
+    let imp3 = q3.price_impact_pct;
 
     let pnl: i128 = back_a as i128 - amt_a as i128 - fee_buf as i128;
 
-    println!(
+    diag!(
         "[B3L CYCLE] {} A={} B={} C={} | back(A)={} fee_buf={} pnl={} thresh={} | impacts=({:.2}bp,{:.2}bp,{:.2}bp)",
         label.unwrap_or(""),
-        a, b, c, back_a, fee_buf, pnl, exec.min_cycle_pnl_lamports,
+        a, b, c, back_a, fee_buf, pnl, thresh,
         bps(imp1), bps(imp2), bps(imp3),
     );
+    evaluated(amt_a, back_a, pnl);
 
-    if bps(imp3) > exec.price_impact_bps_limit as f64 || pnl < exec.min_cycle_pnl_lamports as i128 {
-        println!("{}", "[B3L DECISION] NO-EXEC".red().bold());
+    let profit = if pnl < thresh as i128 { Err(Reject::Profit { pnl, thresh: thresh as i128 }) } else { Ok(()) };
+    let verdict = risk::check_leg(&q3, 3, max_impact_bps).and(profit);
+    if let Err(r) = verdict {
+        diag!("{} {}", "[B3L DECISION] NO-EXEC".red().bold(), r);
+        risk::record(TradePath::ThreeLeg, &key, &r);
         return Ok(());
     }
 
-    if exec.simulate_first {
-        let _ = bincode::deserialize::<VersionedTransaction>(&BASE64_STANDARD.decode(
-            &jup.swap_tx(&q1, &kp.pubkey().to_string(), cfg.fees.priority_fee_lamports).await?,
-        )?)?;
-        println!("{}", "[B3L] simulate_first OK (pe prima tx)".green());
+    execute_three_leg(cfg, jup, rpcs, kp, jito, ledger, [&q1, &q2, &q3], ctx(pnl)).await
+}
+
+/// Construiește cele 3 swap-uri din quote-uri și le trimite ca un singur bundle atomic.
+async fn execute_three_leg(
+    cfg: &Config,
+    jup: &JupiterClient,
+    rpcs: &Arc<RpcRotator>,
+    kp: &Arc<Keypair>,
+    jito: Option<&JitoClient>,
+    ledger: &Arc<PnlLedger>,
+    quotes: [&QuoteResponse; 3],
+    ctx: TradeCtx,
+) -> Result<()> {
+    let exec = &cfg.bundles.as_ref().unwrap().execution;
+    let [q1, q2, q3] = quotes;
+    let user = kp.pubkey().to_string();
+    let mut legs = decode_legs(&[
+        jup.swap_tx(q1, &user, cfg.fees.priority_fee_lamports).await?,
+        jup.swap_tx(q2, &user, cfg.fees.priority_fee_lamports).await?,
+        jup.swap_tx(q3, &user, cfg.fees.priority_fee_lamports).await?,
+    ])?;
+
+    if exec.simulate_first && !simulate_legs(rpcs, kp, &mut legs, cfg.fees.priority_fee_lamports, "B3L").await? {
+        return Ok(());
     }
 
     if !exec.commit || cfg.dry_run {
-        println!("{}", "[B3L] DRY (commit=false sau cfg.dry_run=true) — NU trimit tx".yellow());
+        diag!("{}", "[B3L] DRY (commit=false sau cfg.dry_run=true) — NU trimit tx".yellow());
         return Ok(());
     }
 
-    // Exec doar prima leg (A->B). Restul le lași pe bot să le închidă când redevine profitabil,
-    // ca să eviți 400 la hop-urile următoare.
-    let tx_b64 = jup.swap_tx(&q1, &kp.pubkey().to_string(), cfg.fees.priority_fee_lamports).await?;
-    let sig = send_signed(&client, kp, &tx_b64)?;
-    println!(
-    "{} {} sig={}",
-    "[B3L EXECUTED]".green().bold(),
-    label.unwrap_or(""),
-    sig
+    let Some(jito) = jito else {
+        diag!("{}", "[B3L] Jito OFF — ciclul nu poate fi trimis atomic, skip".yellow());
+        return Ok(());
+    };
+
+    let bundle_id = send_bundle_atomic(rpcs, jito, kp, &legs).await?;
+    diag!(
+    "{} {} bundle={}",
+    "[B3L SENT]".green(),
+    ctx.key,
+    bundle_id.id
     );
+    events::emit(Event::TxSent { path: TradePath::ThreeLeg.as_str(), key: ctx.key.clone(), sig: bundle_id.first_sig.to_string() });
+    spawn_bundle_tracker(rpcs.clone(), ledger.clone(), kp.pubkey(), "B3L", ctx, bundle_id);
 
     Ok(())
 }
 
-fn parse_out(q: &serde_json::Value) -> u64 {
-    q.get("outAmount")
-        .and_then(|x| x.as_str())
-        .and_then(|s| s.parse::<u64>().ok())
-        .unwrap_or(0)
+fn decode_legs(legs_b64: &[String]) -> Result<Vec<VersionedTransaction>> {
+    legs_b64
+        .iter()
+        .map(|b64| -> Result<VersionedTransaction> { Ok(bincode::deserialize(&BASE64_STANDARD.decode(b64)?)?) })
+        .collect()
 }
 
-fn parse_impact(q: &serde_json::Value) -> f64 {
-    q.get("priceImpactPct")
-        .and_then(|x| x.as_str())
-        .and_then(|s| s.parse::<f64>().ok())
-        .unwrap_or(0.0)
-}
+/// simulateTransaction pe primul picior, singurul care pornește din starea curentă a
+/// wallet-ului; o eroare acolo oprește ciclul. Următoarele depind de output-ul celor dinainte
+/// (care nu există încă on-chain), deci nu se simulează și rămân cu compute budget-ul de la
+/// Jupiter. CU măsurat se folosește la dimensionarea priority fee-ului pe primul picior.
+/// Întoarce false dacă ciclul trebuie abandonat.
+async fn simulate_legs(
+    rpcs: &RpcRotator,
+    kp: &Keypair,
+    legs: &mut [VersionedTransaction],
+    priority_lamports: u64,
+    tag: &str,
+) -> Result<bool> {
+    let Some(first) = legs.first_mut() else {
+        return Ok(true);
+    };
+    let rep = simulate::simulate(rpcs, first, &[kp.pubkey()]).await?;
+    if !rep.ok() {
+        diag!(
+            "{} leg#1 err={:?} logs={:?}",
+            format!("[{} SIMULATE] NO-EXEC", tag).red().bold(), rep.err, rep.error_logs(3)
+        );
+        return Ok(false);
+    }
 
-fn parse_amm(q: &serde_json::Value) -> String {
-    q.get("routePlan").and_then(|rp| rp.get(0))
-        .and_then(|r0| r0.get("swapInfo"))
-        .and_then(|si| si.get("label"))
-        .and_then(|l| l.as_str())
-        .unwrap_or("?")
-        .to_string()
+    let resized = match rep.cu_limit() {
+        Some(limit) => simulate::apply_compute_budget(first, limit, priority_lamports),
+        None => false,
+    };
+    diag!(
+        "[{} SIMULATE] leg#1 OK cu={:?} post_sol={:?} cu_budget_resized={}; leg#2..{} nesimulate (depind de leg#1)",
+        tag,
+        rep.units_consumed,
+        rep.post_lamports.first().copied().flatten(),
+        resized,
+        legs.len()
+    );
+    Ok(true)
 }
 
 fn bps(frac: f64) -> f64 {
@@ -283,11 +627,120 @@ fn bps(frac: f64) -> f64 {
     frac * 10_000.0
 }
 
-fn send_signed(client: &RpcClient, kp: &Keypair, tx_b64: &str) -> Result<String> {
-    let tx_bytes = BASE64_STANDARD.decode(tx_b64)?;
-    let mut vtx: VersionedTransaction = bincode::deserialize(&tx_bytes)?;
+/// Bundle acceptat de Block Engine; `first_sig` e semnătura primului picior
+/// (bundle-ul e atomic, deci soarta lui e soarta întregului ciclu).
+struct SentBundle {
+    id: String,
+    first_sig: Signature,
+    /// toate semnăturile (picioare + tip), pentru PnL
+    sigs: Vec<Signature>,
+    last_valid_block_height: u64,
+}
+
+/// Semnează toate picioarele + tx-ul de tip pe același blockhash și le trimite ca un
+/// singur bundle Jito. La eroare reîncearcă (cu blockhash proaspăt) de `max_bundle_retries` ori.
+async fn send_bundle_atomic(
+    rpcs: &RpcRotator,
+    jito: &JitoClient,
+    kp: &Keypair,
+    legs: &[VersionedTransaction],
+) -> Result<SentBundle> {
+    let attempts = jito.max_bundle_retries + 1;
+    let mut last_err = anyhow!("bundle not sent");
+
+    for attempt in 1..=attempts {
+        let (blockhash, last_valid_block_height) = rpcs
+            .call_send(|c| async move {
+                c.get_latest_blockhash_with_commitment(CommitmentConfig::confirmed()).await
+            })
+            .await?;
+
+        let mut txs = Vec::with_capacity(legs.len() + 1);
+        for leg in legs {
+            txs.push(sign_with_blockhash(kp, leg, blockhash));
+        }
+        // tip-ul ultimul: se plătește doar dacă toate picioarele trec
+        txs.push(jito.build_tip_tx(kp, blockhash)?);
+
+        match jito.send_bundle(&txs).await {
+            Ok(id) => {
+                return Ok(SentBundle {
+                    id,
+                    first_sig: txs[0].signatures[0],
+                    sigs: txs.iter().map(|t| t.signatures[0]).collect(),
+                    last_valid_block_height,
+                })
+            }
+            Err(e) => {
+                eprintln!("[JITO] bundle attempt {}/{} failed: {}", attempt, attempts, e);
+                last_err = e;
+                if attempt < attempts {
+                    tokio::time::sleep(Duration::from_millis(200 * attempt as u64)).await;
+                }
+            }
+        }
+    }
+
+    Err(last_err)
+}
+
+/// Urmărește bundle-ul în fundal (prin semnătura primului picior), loghează rezultatul
+/// și trece PnL-ul realizat în ledger.
+fn spawn_bundle_tracker(
+    rpcs: Arc<RpcRotator>,
+    ledger: Arc<PnlLedger>,
+    owner: Pubkey,
+    tag: &'static str,
+    ctx: TradeCtx,
+    bundle: SentBundle,
+) {
+    tokio::spawn(async move {
+        let outcome = confirm::wait_for_outcome(&rpcs, &bundle.first_sig, Some(bundle.last_valid_block_height)).await;
+        if let Ok(o) = &outcome {
+            let path = if tag == "B2L" { TradePath::TwoLeg } else { TradePath::ThreeLeg };
+            events::emit(Event::confirmed(path.as_str(), &ctx.key, &bundle.first_sig, o));
+        }
+        let landed = match outcome {
+            Ok(confirm::TxOutcome::Landed { slot }) => {
+                diag!(
+                    "{} {} bundle={} sig={} slot={}",
+                    format!("[{} EXECUTED]", tag).green().bold(), ctx.key, bundle.id, bundle.first_sig, slot
+                );
+                true
+            }
+            Ok(other @ confirm::TxOutcome::Failed { .. }) => {
+                diag!(
+                    "{} {} bundle={} sig={} {:?}",
+                    format!("[{} {}]", tag, other.tag()).red().bold(), ctx.key, bundle.id, bundle.first_sig, other
+                );
+                false
+            }
+            Ok(other) => {
+                diag!(
+                    "{} {} bundle={} sig={}",
+                    format!("[{} {}]", tag, other.tag()).yellow(), ctx.key, bundle.id, bundle.first_sig
+                );
+                return;
+            }
+            Err(e) => {
+                eprintln!("[{}] bundle={} status necunoscut: {:#}", tag, bundle.id, e);
+                return;
+            }
+        };
+
+        // bundle aterizat (sau eșuat on-chain): citim balanțele reale
+        let sigs = if landed { bundle.sigs.clone() } else { vec![bundle.first_sig] };
+        if let Err(e) = ledger.record(&rpcs, &owner, &ctx, &sigs, landed).await {
+            eprintln!("[PNL] {} bundle={} realized PnL unavailable: {:#}", ctx.key, bundle.id, e);
+        }
+    });
+}
+
+/// Copiază tx-ul de la Jupiter, îi pune `recent_blockhash` comun și îl semnează local.
+fn sign_with_blockhash(kp: &Keypair, leg: &VersionedTransaction, blockhash: Hash) -> VersionedTransaction {
+    let mut vtx = leg.clone();
 
-    // (re)semnează local
+    vtx.message.set_recent_blockhash(blockhash);
     let msg: &VersionedMessage = &vtx.message;
     let sig = kp.sign_message(&msg.serialize());
     if vtx.signatures.is_empty() {
@@ -295,15 +748,5 @@ fn send_signed(client: &RpcClient, kp: &Keypair, tx_b64: &str) -> Result<String>
     } else {
         vtx.signatures[0] = sig;
     }
-
-    let sig_str = client.send_transaction_with_config(
-        &vtx,
-        RpcSendTransactionConfig {
-            skip_preflight: true,
-            max_retries: Some(1),
-            preflight_commitment: Some(CommitmentConfig::confirmed().commitment),
-            ..Default::default()
-        },
-    )?;
-    Ok(sig_str.to_string())
-} 
\ No newline at end of file
+    vtx
+}
@@ -0,0 +1,375 @@
+// src/ata.rs
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_client::rpc_request::TokenAccountsFilter;
+use solana_client::rpc_response::RpcKeyedAccount;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+use std::str::FromStr;
+
+use crate::accounts::{ASSOCIATED_TOKEN_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, WSOL_MINT};
+use crate::config::Config;
+use crate::confirm::{self, TxOutcome};
+use crate::pnl;
+use crate::registry;
+use crate::rpc::RpcRotator;
+
+/// câte CreateIdempotent punem într-o tx (6 conturi fiecare, tx legacy de max 1232 bytes)
+const CREATE_PER_TX: usize = 6;
+/// câte conturi închidem într-o tx (Burn + CloseAccount fiecare)
+const CLOSE_PER_TX: usize = 8;
+/// getMultipleAccounts acceptă max 100 de chei
+const MULTIPLE_ACCOUNTS_CHUNK: usize = 100;
+/// mărimea unui cont de token SPL clasic (pentru estimarea rent-ului)
+const TOKEN_ACCOUNT_LEN: usize = 165;
+/// discriminatorul instrucțiunii CreateIdempotent din programul ATA
+const IX_CREATE_IDEMPOTENT: u8 = 1;
+/// instrucțiunile SPL Token (același layout în Token-2022)
+const IX_TOKEN_BURN: u8 = 8;
+const IX_TOKEN_CLOSE_ACCOUNT: u8 = 9;
+
+/// Un cont de token al wallet-ului, din getTokenAccountsByOwner (jsonParsed).
+#[derive(Debug, Clone)]
+pub struct TokenAccount {
+    pub pubkey: Pubkey,
+    pub mint: String,
+    pub amount: u64,
+    pub decimals: u8,
+    /// lamports în cont (rent; la wSOL și SOL-ul împachetat)
+    pub lamports: u64,
+    /// programul de token care deține contul
+    pub program: Pubkey,
+}
+
+impl TokenAccount {
+    pub fn parse(keyed: &RpcKeyedAccount) -> Option<Self> {
+        let data = serde_json::to_value(&keyed.account.data).ok()?;
+        let info = data.get("parsed")?.get("info")?;
+        let amount = info.get("tokenAmount")?;
+        Some(Self {
+            pubkey: keyed.pubkey.parse().ok()?,
+            mint: info.get("mint")?.as_str()?.to_string(),
+            amount: amount.get("amount")?.as_str()?.parse().ok()?,
+            decimals: u8::try_from(amount.get("decimals")?.as_u64()?).ok()?,
+            lamports: keyed.account.lamports,
+            program: keyed.account.owner.parse().ok()?,
+        })
+    }
+
+    /// rent-ul recuperat la închidere (fără SOL-ul împachetat din wSOL)
+    pub fn rent_lamports(&self) -> u64 {
+        if self.mint == WSOL_MINT {
+            self.lamports.saturating_sub(self.amount)
+        } else {
+            self.lamports
+        }
+    }
+}
+
+/// Toate conturile de token ale wallet-ului: un apel pentru SPL Token și unul pentru Token-2022.
+pub async fn list_token_accounts(client: &RpcClient, owner: &Pubkey) -> Result<Vec<TokenAccount>> {
+    let mut out = Vec::new();
+    for id in [SPL_TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID] {
+        let program = Pubkey::from_str(id)?;
+        let accs = client.get_token_accounts_by_owner(owner, TokenAccountsFilter::ProgramId(program)).await?;
+        out.extend(accs.iter().filter_map(TokenAccount::parse));
+    }
+    Ok(out)
+}
+
+/// Rezultatul unei treceri de închidere a conturilor.
+#[derive(Debug, Clone, Default)]
+pub struct CloseReport {
+    pub closed: u64,
+    /// conturi cu dust ars înainte de închidere
+    pub burned: u64,
+    pub failed: u64,
+    /// rent recuperat (lamports)
+    pub reclaimed_lamports: u64,
+    pub fee_lamports: u64,
+}
+
+/// Rezultatul verificării ATA-urilor.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AtaReport {
+    pub checked: usize,
+    pub existing: usize,
+    /// mints fără ATA (înainte de creare)
+    pub missing: Vec<String>,
+    pub created: Vec<String>,
+    /// mints pentru care crearea a eșuat
+    pub failed: Vec<String>,
+    /// rent blocat efectiv în ATA-urile create (din balanțele on-chain)
+    pub rent_lamports: u64,
+    pub fee_lamports: u64,
+    /// rent estimat pentru `missing`, când nu creăm (dry-run sau create_if_missing=false)
+    pub rent_estimate_lamports: u64,
+}
+
+impl AtaReport {
+    pub fn render(&self) -> String {
+        format!(
+            "checked={} existing={} missing={} created={} failed={} rent={} fee={} rent_estimate={}",
+            self.checked, self.existing, self.missing.len(), self.created.len(), self.failed.len(),
+            self.rent_lamports, self.fee_lamports, self.rent_estimate_lamports
+        )
+    }
+}
+
+fn ata_program() -> Pubkey {
+    Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).expect("ATA program id")
+}
+
+/// Adresa ATA pentru (owner, mint); `token_program` e owner-ul contului de mint
+/// (SPL Token sau Token-2022).
+pub fn ata_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[owner.as_ref(), token_program.as_ref(), mint.as_ref()], &ata_program()).0
+}
+
+/// CreateIdempotent: nu eșuează dacă ATA-ul există deja (ex. creat între verificare și trimitere).
+fn create_idempotent_ix(payer: &Pubkey, owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ata_program(),
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(ata_address(owner, mint, token_program), false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data: vec![IX_CREATE_IDEMPOTENT],
+    }
+}
+
+/// Mints pentru care vrem ATA: `account_setup.mints` + perechi + bundle-uri (+ tri_scan).
+/// wSOL nu intră: Jupiter îl împachetează/despachetează singur.
+pub fn setup_mints(cfg: &Config) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut add = |m: &str| {
+        if m != WSOL_MINT && !out.iter().any(|x| x == m) {
+            out.push(m.to_string());
+        }
+    };
+
+    if let Some(s) = &cfg.account_setup {
+        s.mints.iter().for_each(|m| add(m));
+    }
+    for p in &cfg.pairs {
+        add(&p.input_mint);
+        add(&p.output_mint);
+    }
+    if let Some(b) = &cfg.bundles {
+        for bl in &b.two_leg {
+            add(&bl.from);
+            add(&bl.to);
+        }
+        b.tri_leg.iter().flat_map(|bl| &bl.legs).for_each(|m| add(m));
+        if let Some(ts) = b.tri_scan.as_ref().filter(|ts| ts.enabled) {
+            for sym in &ts.tokens {
+                match registry::global().resolve(sym) {
+                    Ok(t) => add(&t.mint),
+                    Err(e) => eprintln!("[ATA] {:#}", e),
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Verifică ATA-urile wallet-ului pentru `mints` și, dacă `create`, le creează pe cele lipsă
+/// în loturi de câte `CREATE_PER_TX`. Rent-ul raportat e cel real (din tx-urile confirmate).
+pub async fn ensure_atas(rpcs: &RpcRotator, kp: &Keypair, mints: &[String], create: bool) -> Result<AtaReport> {
+    let owner = kp.pubkey();
+    let mut report = AtaReport::default();
+
+    let mut parsed: Vec<Pubkey> = Vec::new();
+    for m in mints {
+        match m.parse::<Pubkey>() {
+            Ok(pk) => parsed.push(pk),
+            Err(e) => eprintln!("[ATA] invalid mint {}: {}", m, e),
+        }
+    }
+
+    // programul de token al fiecărui mint = owner-ul contului de mint
+    let mut targets: Vec<(Pubkey, Pubkey, Pubkey)> = Vec::new(); // (mint, token_program, ata)
+    for chunk in parsed.chunks(MULTIPLE_ACCOUNTS_CHUNK) {
+        let accounts = rpcs.call(|c| async move { c.get_multiple_accounts(chunk).await }).await?;
+        for (mint, acc) in chunk.iter().zip(accounts) {
+            let Some(acc) = acc else {
+                eprintln!("[ATA] mint {} not found on-chain, skip", mint);
+                continue;
+            };
+            targets.push((*mint, acc.owner, ata_address(&owner, mint, &acc.owner)));
+        }
+    }
+    report.checked = targets.len();
+
+    let mut missing: Vec<(Pubkey, Pubkey, Pubkey)> = Vec::new();
+    for chunk in targets.chunks(MULTIPLE_ACCOUNTS_CHUNK) {
+        let keys: Vec<Pubkey> = chunk.iter().map(|t| t.2).collect();
+        let keys = &keys;
+        let accounts = rpcs.call(|c| async move { c.get_multiple_accounts(keys).await }).await?;
+        for (t, acc) in chunk.iter().zip(accounts) {
+            if acc.is_some() {
+                report.existing += 1;
+            } else {
+                missing.push(*t);
+            }
+        }
+    }
+    report.missing = missing.iter().map(|t| t.0.to_string()).collect();
+
+    if missing.is_empty() {
+        return Ok(report);
+    }
+    if !create {
+        let rent = rpcs
+            .call(|c| async move { c.get_minimum_balance_for_rent_exemption(TOKEN_ACCOUNT_LEN).await })
+            .await?;
+        report.rent_estimate_lamports = rent * missing.len() as u64;
+        return Ok(report);
+    }
+
+    for batch in missing.chunks(CREATE_PER_TX) {
+        let names: Vec<String> = batch.iter().map(|t| t.0.to_string()).collect();
+        match create_batch(rpcs, kp, batch).await {
+            Ok((rent, fee)) => {
+                diag!("[ATA] created {} ATA(s) rent={} fee={}", batch.len(), rent, fee);
+                report.rent_lamports += rent;
+                report.fee_lamports += fee;
+                report.created.extend(names);
+            }
+            Err(e) => {
+                eprintln!("[ATA] batch {:?} failed: {:#}", names, e);
+                report.failed.extend(names);
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Trimite un lot de CreateIdempotent și întoarce (rent, fee) din delta on-chain a wallet-ului.
+async fn create_batch(rpcs: &RpcRotator, kp: &Keypair, batch: &[(Pubkey, Pubkey, Pubkey)]) -> Result<(u64, u64)> {
+    let owner = kp.pubkey();
+    let ixs: Vec<Instruction> = batch
+        .iter()
+        .map(|(mint, token_program, _)| create_idempotent_ix(&owner, &owner, mint, token_program))
+        .collect();
+
+    let sig = send_and_confirm(rpcs, kp, &ixs).await?;
+    diag!("[ATA] sig={}", sig);
+
+    // SOL ieșit din wallet = rent + fee
+    let delta = pnl::fetch_delta(rpcs, &sig, &owner).await?;
+    let spent = (-delta.sol_delta).max(0) as u64;
+    Ok((spent.saturating_sub(delta.fee_lamports), delta.fee_lamports))
+}
+
+/// Închide conturile în loturi; cele cu sold (dust) se ard întâi, cu excepția wSOL
+/// (CloseAccount pe wSOL despachetează soldul în SOL nativ).
+pub async fn close_accounts(rpcs: &RpcRotator, kp: &Keypair, accounts: &[TokenAccount]) -> CloseReport {
+    let owner = kp.pubkey();
+    let mut report = CloseReport::default();
+
+    for batch in accounts.chunks(CLOSE_PER_TX) {
+        let mut ixs: Vec<Instruction> = Vec::new();
+        let mut included: Vec<&TokenAccount> = Vec::new();
+        let mut burned = 0;
+        for acc in batch {
+            if acc.amount > 0 && acc.mint != WSOL_MINT {
+                match burn_ix(acc, &owner) {
+                    Ok(ix) => ixs.push(ix),
+                    Err(e) => {
+                        eprintln!("[CLEANUP] {} skip: {:#}", acc.pubkey, e);
+                        report.failed += 1;
+                        continue;
+                    }
+                }
+                burned += 1;
+            }
+            ixs.push(close_ix(acc, &owner));
+            included.push(acc);
+        }
+        if included.is_empty() {
+            continue;
+        }
+
+        match send_and_confirm(rpcs, kp, &ixs).await {
+            Ok(sig) => {
+                let rent: u64 = included.iter().map(|a| a.rent_lamports()).sum();
+                let fee = pnl::fetch_delta(rpcs, &sig, &owner).await.map(|d| d.fee_lamports).unwrap_or(0);
+                diag!("[CLEANUP] closed {} account(s) burned={} rent={} fee={} sig={}", included.len(), burned, rent, fee, sig);
+                report.closed += included.len() as u64;
+                report.burned += burned;
+                report.reclaimed_lamports += rent;
+                report.fee_lamports += fee;
+            }
+            Err(e) => {
+                eprintln!("[CLEANUP] close batch failed: {:#}", e);
+                report.failed += included.len() as u64;
+            }
+        }
+    }
+    report
+}
+
+fn burn_ix(acc: &TokenAccount, owner: &Pubkey) -> Result<Instruction> {
+    let mut data = vec![IX_TOKEN_BURN];
+    data.extend_from_slice(&acc.amount.to_le_bytes());
+    Ok(Instruction {
+        program_id: acc.program,
+        accounts: vec![
+            AccountMeta::new(acc.pubkey, false),
+            AccountMeta::new(acc.mint.parse::<Pubkey>()?, false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+        data,
+    })
+}
+
+fn close_ix(acc: &TokenAccount, owner: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: acc.program,
+        accounts: vec![
+            AccountMeta::new(acc.pubkey, false),
+            AccountMeta::new(*owner, false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+        data: vec![IX_TOKEN_CLOSE_ACCOUNT],
+    }
+}
+
+/// Semnează cu wallet-ul (fee payer), trimite cu preflight și așteaptă confirmarea.
+async fn send_and_confirm(rpcs: &RpcRotator, kp: &Keypair, ixs: &[Instruction]) -> Result<Signature> {
+    let owner = kp.pubkey();
+    let (blockhash, last_valid_block_height) = rpcs
+        .call_send(|c| async move { c.get_latest_blockhash_with_commitment(CommitmentConfig::confirmed()).await })
+        .await?;
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&owner), &[kp], blockhash);
+    let tx = &tx;
+    let sig = rpcs
+        .call_send(|c| async move {
+            c.send_transaction_with_config(
+                tx,
+                RpcSendTransactionConfig {
+                    skip_preflight: false,
+                    preflight_commitment: Some(CommitmentConfig::confirmed().commitment),
+                    ..Default::default()
+                },
+            )
+            .await
+        })
+        .await?;
+
+    match confirm::wait_for_outcome(rpcs, &sig, Some(last_valid_block_height)).await? {
+        TxOutcome::Landed { .. } => Ok(sig),
+        other => Err(anyhow!("sig={} {}", sig, other.tag())),
+    }
+}
@@ -1,7 +1,11 @@
 // src/cache.rs
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use dashmap::DashMap;
-use serde_json::Value;
+use tokio::sync::OnceCell;
+
+use crate::jupiter::QuoteResponse;
 
 /// Cheie = (input_mint, output_mint, amount, only_direct, slippage_bps)
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -13,40 +17,240 @@ pub struct QuoteKey {
     pub slippage_bps: u64,
 }
 
-#[derive(Clone)]
+/// Contoare cumulative, pentru rezumatul orar.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// cereri care au așteptat un fetch identic deja în zbor (și nu au mai lovit HTTP)
+    pub coalesced: u64,
+    pub evictions: u64,
+    pub entries: usize,
+}
+
+impl CacheStats {
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 { 0.0 } else { self.hits as f64 / total as f64 }
+    }
+}
+
+/// Rezultatul unui fetch în zbor, împărțit de toate cererile identice (și eroarea).
+type Inflight = Arc<OnceCell<Result<QuoteResponse, Arc<anyhow::Error>>>>;
+
+/// Scoate intrarea din `inflight` când cererea se termină sau e anulată (drop),
+/// dar doar dacă rezultatul e gata sau nu mai așteaptă nimeni altcineva pe ea.
+struct InflightGuard<'a> {
+    map: &'a DashMap<QuoteKey, Inflight>,
+    key: &'a QuoteKey,
+    cell: &'a Inflight,
+}
+
+impl Drop for InflightGuard<'_> {
+    fn drop(&mut self) {
+        // 2 = clona din map + a noastră
+        self.map.remove_if(self.key, |_, c| {
+            Arc::ptr_eq(c, self.cell) && (c.initialized() || Arc::strong_count(c) <= 2)
+        });
+    }
+}
+
 pub struct QuoteCache {
     ttl: Duration,
-    map: DashMap<QuoteKey, (Value, Instant)>,
+    map: DashMap<QuoteKey, (QuoteResponse, Instant)>,
+    /// un rezultat partajat per cheie aflată în fetch; cererile identice așteaptă pe el
+    inflight: DashMap<QuoteKey, Inflight>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    coalesced: AtomicU64,
+    evictions: AtomicU64,
 }
 
 impl QuoteCache {
-    /// ttl_secs: de ex. 2 sec (industrial: 1–3s ca să reduci 429)
-    pub fn new(ttl_secs: u64) -> Self {
+    /// ttl_ms: de ex. 2000 ms (industrial: 1–3s ca să reduci 429)
+    pub fn new(ttl_ms: u64) -> Self {
         Self {
-            ttl: Duration::from_secs(ttl_secs),
+            ttl: Duration::from_millis(ttl_ms),
             map: DashMap::new(),
+            inflight: DashMap::new(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            coalesced: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
         }
     }
 
     /// Dacă avem un quote proaspăt, îl returnăm (clone).
-    pub fn get_if_fresh(&self, key: &QuoteKey) -> Option<Value> {
+    pub fn get_if_fresh(&self, key: &QuoteKey) -> Option<QuoteResponse> {
         if let Some(entry) = self.map.get(key) {
-            let (_v, t) = &*entry;
+            let (v, t) = &*entry;
             if t.elapsed() < self.ttl {
-                return Some(entry.value().0.clone());
+                return Some(v.clone());
             }
         }
         None
     }
 
     /// Salvează/actualizează quote-ul.
-    pub fn put(&self, key: QuoteKey, value: Value) {
+    pub fn put(&self, key: QuoteKey, value: QuoteResponse) {
         self.map.insert(key, (value, Instant::now()));
     }
 
-    /// Curăță intrările expirate (opțional; nu e obligatoriu s-o chemi des).
+    /// Întoarce quote-ul din cache sau îl obține cu `fetch`. Cererile concurente pentru
+    /// aceeași cheie împart un singur fetch și rezultatul lui, inclusiv eroarea. Dacă cererea
+    /// care face fetch-ul e anulată, una dintre cele care așteaptă îl preia.
+    pub async fn get_or_fetch<F, Fut>(&self, key: QuoteKey, fetch: F) -> anyhow::Result<QuoteResponse>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = anyhow::Result<QuoteResponse>>,
+    {
+        if let Some(q) = self.get_if_fresh(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(q);
+        }
+
+        // guard-ul DashMap nu trebuie ținut peste await -> clonăm Arc-ul
+        let cell = self.inflight.entry(key.clone()).or_default().clone();
+        let _guard = InflightGuard { map: &self.inflight, key: &key, cell: &cell };
+
+        let mut leader = false;
+        let res = cell
+            .get_or_init(|| {
+                leader = true;
+                async {
+                    self.misses.fetch_add(1, Ordering::Relaxed);
+                    let res = fetch().await;
+                    if let Ok(q) = &res {
+                        self.put(key.clone(), q.clone());
+                    }
+                    res.map_err(Arc::new)
+                }
+            })
+            .await;
+        if !leader {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            self.coalesced.fetch_add(1, Ordering::Relaxed);
+        }
+        res.clone().map_err(|e| anyhow::anyhow!("{:#}", e))
+    }
+
+    /// Curăță intrările expirate (chemată periodic din main).
     pub fn gc(&self) {
         let ttl = self.ttl;
-        self.map.retain(|, (, t)| t.elapsed() < ttl);
+        let mut evicted = 0u64;
+        self.map.retain(|_, (_, t)| {
+            let keep = t.elapsed() < ttl;
+            if !keep {
+                evicted += 1;
+            }
+            keep
+        });
+        self.evictions.fetch_add(evicted, Ordering::Relaxed);
     }
-}
\ No newline at end of file
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: self.map.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    fn key() -> QuoteKey {
+        QuoteKey {
+            input: "So11111111111111111111111111111111111111112".into(),
+            output: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".into(),
+            amount: 1_000_000,
+            only_direct: None,
+            slippage_bps: 50,
+        }
+    }
+
+    fn quote(out: u64) -> QuoteResponse {
+        serde_json::from_value(serde_json::json!({
+            "inputMint": "So11111111111111111111111111111111111111112",
+            "inAmount": "1000000",
+            "outputMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "outAmount": out.to_string(),
+            "otherAmountThreshold": out.to_string(),
+            "priceImpactPct": "0",
+            "routePlan": []
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_share_one_fetch() {
+        let c = QuoteCache::new(2_000);
+        let calls = AtomicUsize::new(0);
+        let fetch = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(quote(42))
+        };
+        let (a, b) = tokio::join!(c.get_or_fetch(key(), fetch), c.get_or_fetch(key(), fetch));
+        assert_eq!((a.unwrap().out_amount, b.unwrap().out_amount), (42, 42));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let s = c.stats();
+        assert_eq!((s.misses, s.hits, s.coalesced), (1, 1, 1));
+        assert!(c.inflight.is_empty());
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_share_the_error() {
+        let c = QuoteCache::new(2_000);
+        let calls = AtomicUsize::new(0);
+        let fetch = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Err(anyhow::anyhow!("429 Too Many Requests"))
+        };
+        let (a, b) = tokio::join!(c.get_or_fetch(key(), fetch), c.get_or_fetch(key(), fetch));
+        assert!(a.unwrap_err().to_string().contains("429"));
+        assert!(b.unwrap_err().to_string().contains("429"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(c.inflight.is_empty());
+        assert!(c.get_if_fresh(&key()).is_none());
+    }
+
+    #[tokio::test]
+    async fn cancelled_fetch_does_not_leak_inflight() {
+        let c = QuoteCache::new(2_000);
+        let slow = || async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(quote(1))
+        };
+        let r = tokio::time::timeout(Duration::from_millis(10), c.get_or_fetch(key(), slow)).await;
+        assert!(r.is_err());
+        assert!(c.inflight.is_empty());
+
+        let q = c.get_or_fetch(key(), || async { Ok(quote(7)) }).await.unwrap();
+        assert_eq!(q.out_amount, 7);
+    }
+
+    #[tokio::test]
+    async fn waiter_takes_over_a_cancelled_fetch() {
+        let c = QuoteCache::new(2_000);
+        let slow = || async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(quote(1))
+        };
+        let leader = tokio::time::timeout(Duration::from_millis(10), c.get_or_fetch(key(), slow));
+        let waiter = async {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            c.get_or_fetch(key(), || async { Ok(quote(9)) }).await
+        };
+        let (l, w) = tokio::join!(leader, waiter);
+        assert!(l.is_err());
+        assert_eq!(w.unwrap().out_amount, 9);
+        assert!(c.inflight.is_empty());
+    }
+}
@@ -0,0 +1,138 @@
+// src/cli.rs
+use anyhow::{anyhow, bail, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub const USAGE: &str = "\
+SolProRunner — Jupiter v6 arb bot
+
+USAGE:
+    solpro-runner-rs [COMMAND] [OPTIONS]
+
+COMMANDS:
+    run                          bot complet (implicit)
+    scan                         o trecere de rute triunghiulare + bundles, fără tranzacții
+    quote <IN> <OUT> <AMOUNT>    quote Jupiter; IN/OUT = simbol sau mint, AMOUNT în unități UI
+    balances                     balanța SOL + tokenurile cunoscute
+    unwind --token <X>           vinde tokenul X (simbol sau mint) în auto_unwind.base_mint
+    validate-config              validează config-ul și iese
+
+OPTIONS:
+    --config <PATH>              fișierul de config (implicit config.json)
+    --dry-run                    forțează dry_run=true (și după hot-reload)
+    --format <text|json>         formatul output-ului (--json = --format json)
+    -h, --help                   acest mesaj
+";
+
+/// `--format json`: stdout e rezervat documentului JSON, diagnosticele (`diag!`) merg pe stderr.
+static JSON_STDOUT: AtomicBool = AtomicBool::new(false);
+
+pub fn set_json_stdout(on: bool) {
+    JSON_STDOUT.store(on, Ordering::Relaxed);
+}
+
+pub fn json_stdout() -> bool {
+    JSON_STDOUT.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone)]
+pub enum Command {
+    Run,
+    Scan,
+    Quote { input: String, output: String, amount_ui: f64 },
+    Balances,
+    Unwind { token: String },
+    ValidateConfig,
+    Help,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cli {
+    pub command: Command,
+    pub config_path: String,
+    pub dry_run: bool,
+    pub format: OutputFormat,
+}
+
+impl Cli {
+    pub fn from_env() -> Result<Self> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    /// Parsează argumentele (fără numele binarului). Fără subcomandă = `run`.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self> {
+        let mut config_path = "config.json".to_string();
+        let mut dry_run = false;
+        let mut format = OutputFormat::Text;
+        let mut token: Option<String> = None;
+        let mut help = false;
+        let mut positional: Vec<String> = Vec::new();
+
+        let mut it = args.into_iter();
+        while let Some(arg) = it.next() {
+            // acceptă și forma --flag=valoare
+            let (flag, inline) = match arg.split_once('=') {
+                Some((f, v)) if f.starts_with("--") => (f.to_string(), Some(v.to_string())),
+                _ => (arg.clone(), None),
+            };
+            let mut value = |name: &str| -> Result<String> {
+                inline
+                    .clone()
+                    .or_else(|| it.next())
+                    .ok_or_else(|| anyhow!("{} needs a value", name))
+            };
+
+            match flag.as_str() {
+                "--config" | "-c" => config_path = value("--config")?,
+                "--dry-run" => dry_run = true,
+                "--format" => {
+                    format = match value("--format")?.as_str() {
+                        "text" => OutputFormat::Text,
+                        "json" => OutputFormat::Json,
+                        other => bail!("unknown --format {:?} (expected text | json)", other),
+                    }
+                }
+                "--json" => format = OutputFormat::Json,
+                "--token" => token = Some(value("--token")?),
+                "-h" | "--help" => help = true,
+                f if f.starts_with('-') && f.parse::<f64>().is_err() => bail!("unknown option {}\n\n{}", f, USAGE),
+                _ => positional.push(arg),
+            }
+        }
+
+        let command = if help {
+            Command::Help
+        } else {
+            let mut pos = positional.into_iter();
+            match pos.next().as_deref() {
+                None | Some("run") => Command::Run,
+                Some("scan") => Command::Scan,
+                Some("balances") => Command::Balances,
+                Some("validate-config") => Command::ValidateConfig,
+                Some("unwind") => Command::Unwind {
+                    token: token.or_else(|| pos.next()).ok_or_else(|| anyhow!("unwind needs --token <X>"))?,
+                },
+                Some("quote") => {
+                    let (Some(input), Some(output), Some(amount)) = (pos.next(), pos.next(), pos.next()) else {
+                        bail!("usage: quote <IN> <OUT> <AMOUNT>");
+                    };
+                    let amount_ui: f64 = amount
+                        .parse()
+                        .map_err(|_| anyhow!("invalid amount {:?}", amount))?;
+                    if !amount_ui.is_finite() || amount_ui <= 0.0 {
+                        bail!("amount must be > 0");
+                    }
+                    Command::Quote { input, output, amount_ui }
+                }
+                Some(other) => bail!("unknown command {:?}\n\n{}", other, USAGE),
+            }
+        };
+
+        Ok(Self { command, config_path, dry_run, format })
+    }
+}
@@ -1,7 +1,9 @@
 // src/config.rs
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use std::{fs, path::Path};
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::validate::{self, ConfigError, Report};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -10,6 +12,10 @@ pub struct Config {
     // Jupiter
     pub jupiter_base: String,
     pub prefer_orca: bool,
+    #[serde(default)]
+    pub quote_cache: QuoteCacheConfig,
+    #[serde(default)]
+    pub resolver: ResolverConfig,
     pub max_slippage_bps: u64,
     pub min_profit_bps: u64,
     #[serde(default)]
@@ -26,10 +32,22 @@ pub struct Config {
     // perechi
     pub pairs: Vec<Pair>,
 
+    // limite de risc (bps), comune pentru toate căile de execuție
+    #[serde(default)]
+    pub risk: RiskConfig,
+
     // === Bundles (2-leg / 3+-leg / exec) ===
     #[serde(default)]
     pub bundles: Option<BundlesConfig>,
 
+    // ATA-uri pregătite la pornire (altfel primul swap plătește rent-ul în tx-ul de arb)
+    #[serde(default)]
+    pub account_setup: Option<AccountSetupConfig>,
+
+    // închiderea periodică a conturilor de token goale / cu dust
+    #[serde(default)]
+    pub cleanup: Option<CleanupConfig>,
+
     // token map & markets (opționale)
     #[serde(default)]
     pub tokens: Option<serde_json::Map<String, serde_json::Value>>,
@@ -55,9 +73,19 @@ pub struct Config {
     #[serde(default)]
     pub auto_unwind: Option<AutoUnwindCfg>,
 
-    // diverse opționale
+    // hot-reload config.json
+    #[serde(default)]
+    pub reload: ReloadConfig,
+
+    // jurnal de evenimente JSON-lines (tracing)
+    #[serde(default)]
+    pub logs: Option<LogsConfig>,
+
+    // endpoint Prometheus
     #[serde(default)]
-    pub logs: Option<serde_json::Value>,
+    pub metrics: Option<MetricsConfig>,
+
+    // diverse opționale
     #[serde(default)]
     pub diagnostics: Option<serde_json::Value>,
 }
@@ -70,6 +98,50 @@ pub struct MinProfitCfg {
     pub denom: Option<String>, // ex. "SOL" dacă mode="abs"
 }
 
+/// Cache pentru /quote (TTL scurt, ca să reducem 429 pe endpoint-ul public).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteCacheConfig {
+    #[serde(default = "default_quote_cache_ttl_ms")] pub ttl_ms: u64,
+    #[serde(default = "default_quote_cache_gc_ms")] pub gc_every_ms: u64,
+}
+
+impl Default for QuoteCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_ms: default_quote_cache_ttl_ms(),
+            gc_every_ms: default_quote_cache_gc_ms(),
+        }
+    }
+}
+
+fn default_quote_cache_ttl_ms() -> u64 { 2_000 }
+fn default_quote_cache_gc_ms() -> u64 { 30_000 }
+
+/// Lista de tokeni Jupiter: snapshot local + refresh periodic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolverConfig {
+    #[serde(default = "default_resolver_url")] pub url: String,
+    #[serde(default = "default_resolver_snapshot")] pub snapshot_path: String,
+    #[serde(default = "default_resolver_refresh_ms")] pub refresh_every_ms: u64,
+    #[serde(default = "default_resolver_timeout_ms")] pub timeout_ms: u64,
+}
+
+impl Default for ResolverConfig {
+    fn default() -> Self {
+        Self {
+            url: default_resolver_url(),
+            snapshot_path: default_resolver_snapshot(),
+            refresh_every_ms: default_resolver_refresh_ms(),
+            timeout_ms: default_resolver_timeout_ms(),
+        }
+    }
+}
+
+fn default_resolver_url() -> String { "https://token.jup.ag/all".to_string() }
+fn default_resolver_snapshot() -> String { "cache/jupiter_tokens.json".to_string() }
+fn default_resolver_refresh_ms() -> u64 { 6 * 3_600_000 }
+fn default_resolver_timeout_ms() -> u64 { 20_000 }
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AggressiveConfig {
     pub enabled: bool,
@@ -86,6 +158,17 @@ pub struct Pair {
     pub output_mint: String,
     #[serde(default)]
     pub label: Option<String>,
+
+    // override-uri per pereche (lipsă = valorile globale)
+    /// notional în unități UI ale lui inputMint (ex. 25.0 USDC)
+    #[serde(default)]
+    pub notional: Option<f64>,
+    #[serde(default)]
+    pub min_profit_bps: Option<u64>,
+    #[serde(default)]
+    pub max_price_impact_bps: Option<f64>,
+    #[serde(default)]
+    pub slippage_bps: Option<u64>,
 }
 
 /* ===================== Bundles ===================== */
@@ -104,8 +187,57 @@ pub struct BundlesConfig {
     /// Setări de execuție pentru bundle-uri
     #[serde(default)]
     pub execution: BundleExecConfig,
+
+    /// Scanare triunghiulară dinamică (simboluri rezolvate prin TokenRegistry)
+    #[serde(default)]
+    pub tri_scan: Option<TriScanConfig>,
+
+    /// Căutarea mărimii optime per ciclu (fără secțiune = notional fix)
+    #[serde(default)]
+    pub sizing: Option<SizingConfig>,
+}
+
+/// Profitul unui ciclu e concav în mărime; în loc de un singur notional cotăm mai multe
+/// mărimi în jurul lui și o executăm pe cea cu net maxim.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// "ladder" (scară geometrică min..max) sau "golden" (golden-section pe log(mărime))
+    #[serde(default = "default_sizing_mode")]
+    pub mode: String,
+    /// limitele căutării, ca multiplu al notional-ului de bază
+    #[serde(default = "default_sizing_min_mult")]
+    pub min_mult: f64,
+    #[serde(default = "default_sizing_max_mult")]
+    pub max_mult: f64,
+    /// câte mărimi are scara (doar pentru "ladder")
+    #[serde(default = "default_sizing_ladder_steps")]
+    pub ladder_steps: usize,
+    /// buget de quote-uri per ciclu (un ciclu pe 3 picioare consumă 3 la fiecare mărime)
+    #[serde(default = "default_sizing_max_quotes")]
+    pub max_quotes: usize,
+}
+
+fn default_sizing_mode() -> String { "ladder".into() }
+fn default_sizing_min_mult() -> f64 { 0.25 }
+fn default_sizing_max_mult() -> f64 { 4.0 }
+fn default_sizing_ladder_steps() -> usize { 5 }
+fn default_sizing_max_quotes() -> usize { 12 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriScanConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Simboluri; primul e baza ciclului (trebuie SOL), ex. ["SOL","USDT","BONK","mSOL"]
+    pub tokens: Vec<String>,
+    /// Câte oportunități (cele mai bune) se execută pe o trecere
+    #[serde(default = "default_tri_max_execs")]
+    pub max_execs_per_pass: usize,
 }
 
+fn default_tri_max_execs() -> usize { 1 }
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TwoLegBundle {
     /// Etichetă/nume (poate veni ca "name" sau "label")
@@ -115,6 +247,10 @@ pub struct TwoLegBundle {
     /// Mints: from -> to
     pub from: String,
     pub to: String,
+
+    /// override pentru risk.two_leg_max_impact_bps
+    #[serde(default)]
+    pub max_price_impact_bps: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -133,6 +269,10 @@ pub struct BundlePath {
     pub from: Option<String>,
     #[serde(default)]
     pub to: Option<String>,
+
+    /// override pentru risk.three_leg_max_impact_bps
+    #[serde(default)]
+    pub max_price_impact_bps: Option<f64>,
 }
 
 /* -------- Exec config pentru Bundles -------- */
@@ -185,8 +325,21 @@ pub struct RpcConfig {
     pub encoding_json: Option<String>,
     #[serde(default)]
     pub http_headers: Option<serde_json::Value>,
+    /// cât stă un endpoint scos din rotație după timeout/429/5xx
+    #[serde(default = "default_rpc_cooldown_ms")]
+    pub cooldown_ms: u64,
+    /// cât de des se face getSlot pe toate endpoint-urile
+    #[serde(default = "default_rpc_probe_every_ms")]
+    pub probe_every_ms: u64,
+    /// câte sloturi poate fi în urmă un endpoint ca să mai fie folosit la citiri
+    #[serde(default = "default_rpc_max_slot_lag")]
+    pub max_slot_lag: u64,
 }
 
+fn default_rpc_cooldown_ms() -> u64 { 30_000 }
+fn default_rpc_probe_every_ms() -> u64 { 15_000 }
+fn default_rpc_max_slot_lag() -> u64 { 25 }
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Fees {
     pub lamports_per_signature: u64,
@@ -208,13 +361,145 @@ pub struct JitoConfig {
 fn default_tip_lamports() -> u64 { 100_000 }
 fn default_max_bundle_retries() -> u32 { 2 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LimiterConfig {
     pub rps: u32,
     pub burst: u32,
     pub jitter_ms: u64,
 }
 
+/// Watcher pe config.json: la salvare se re-validează și se înlocuiește config-ul activ.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReloadConfig {
+    #[serde(default = "default_reload_enabled")] pub enabled: bool,
+    #[serde(default = "default_reload_check_ms")] pub check_every_ms: u64,
+}
+
+impl Default for ReloadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_reload_enabled(),
+            check_every_ms: default_reload_check_ms(),
+        }
+    }
+}
+
+fn default_reload_enabled() -> bool { true }
+fn default_reload_check_ms() -> u64 { 2_000 }
+
+/// Limite de impact per picior, toate în bps (1bp = 0.01%), pe cale de execuție.
+/// Override-urile din `pairs[]` / bundle-uri au prioritate; vezi risk::max_impact_bps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskConfig {
+    #[serde(default = "default_risk_pair_bps")]
+    pub pair_max_impact_bps: f64,
+    #[serde(default = "default_risk_unwind_bps")]
+    pub unwind_max_impact_bps: f64,
+    /// lipsă = bundles.execution.price_impact_bps_limit
+    #[serde(default)]
+    pub two_leg_max_impact_bps: Option<f64>,
+    #[serde(default)]
+    pub three_leg_max_impact_bps: Option<f64>,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            pair_max_impact_bps: default_risk_pair_bps(),
+            unwind_max_impact_bps: default_risk_unwind_bps(),
+            two_leg_max_impact_bps: None,
+            three_leg_max_impact_bps: None,
+        }
+    }
+}
+
+fn default_risk_pair_bps() -> f64 { 10.0 }
+fn default_risk_unwind_bps() -> f64 { 30.0 }
+
+/// Verificarea/crearea ATA-urilor wallet-ului; vezi ata::ensure_atas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSetupConfig {
+    /// verifică ATA-urile pentru `mints` + mint-urile din pairs/bundles
+    #[serde(default)]
+    pub ensure_atas: bool,
+    /// creează ATA-urile lipsă (altfel doar raportează rent-ul estimat)
+    #[serde(default)]
+    pub create_if_missing: bool,
+    #[serde(default = "default_account_setup_on_startup")]
+    pub on_startup: bool,
+    /// mints suplimentare (în afară de cele din pairs/bundles)
+    #[serde(default)]
+    pub mints: Vec<String>,
+}
+
+fn default_account_setup_on_startup() -> bool { true }
+
+/// Întreținere: închide conturile de token goale (rent înapoi) și curăță dust-ul.
+/// ATA-urile mint-urilor din account_setup/pairs/bundles și tokenii din auto_unwind.deny rămân.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_cleanup_every_ms")]
+    pub every_ms: u64,
+    /// sub valoarea asta (lamports, din quote-ul token→SOL) un sold e dust
+    #[serde(default = "default_cleanup_dust_max")]
+    pub dust_max_lamports: u64,
+    /// dust care valorează cel puțin atât se vinde (swap final); sub, se arde și contul se închide
+    #[serde(default = "default_cleanup_sweep_min")]
+    pub sweep_min_lamports: u64,
+    /// închide și ATA-urile mint-urilor configurate (se vor recrea, cu rent, la următorul swap)
+    #[serde(default)]
+    pub close_configured: bool,
+}
+
+fn default_cleanup_every_ms() -> u64 { 3_600_000 }
+fn default_cleanup_dust_max() -> u64 { 100_000 }
+fn default_cleanup_sweep_min() -> u64 { 20_000 }
+
+/// Evenimentele de trading (quote, evaluare ciclu, skip, tx trimisă/confirmată, unwind)
+/// ca JSON-lines în `dir`, câte un fișier pe zi/oră, rotit și la `max_file_mb`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogsConfig {
+    #[serde(default)]
+    pub json_events: bool,
+    #[serde(default = "default_logs_dir")]
+    pub dir: String,
+    /// "daily" | "hourly"
+    #[serde(default = "default_logs_rotate")]
+    pub rotate: String,
+    /// 0 = fără limită de mărime
+    #[serde(default = "default_logs_max_file_mb")]
+    pub max_file_mb: u64,
+    /// câte fișiere se păstrează (cele mai vechi se șterg); 0 = toate
+    #[serde(default = "default_logs_keep_files")]
+    pub keep_files: usize,
+    /// include și QuoteFetched (câte unul per request /quote; volum mare)
+    #[serde(default)]
+    pub quotes: bool,
+}
+
+fn default_logs_dir() -> String { "logs/events".into() }
+fn default_logs_rotate() -> String { "daily".into() }
+fn default_logs_max_file_mb() -> u64 { 256 }
+fn default_logs_keep_files() -> usize { 14 }
+
+/// Server HTTP cu metrici Prometheus pe `listen` (GET /metrics). Fără autentificare:
+/// lăsați-l pe loopback sau în spatele unui proxy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_metrics_listen")]
+    pub listen: String,
+    /// cât de des se citesc balanțele wallet-ului pentru gauge; 0 = deloc
+    #[serde(default = "default_metrics_balances_every_ms")]
+    pub balances_every_ms: u64,
+}
+
+fn default_metrics_listen() -> String { "127.0.0.1:9184".into() }
+fn default_metrics_balances_every_ms() -> u64 { 30_000 }
+
 // Auto-unwind
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutoUnwindCfg {
@@ -224,12 +509,62 @@ pub struct AutoUnwindCfg {
     pub mode: String,        // "always" | "pnl_gt_0" | "bps"
     pub min_profit_bps: u64, // folosit doar dacă mode="bps"
     pub check_every_ms: u64, // cât de des verifici balanțele pentru unwind
+    /// după atâtea ms de la achiziție se vinde oricum (ca "always"); lipsă = niciodată
+    #[serde(default)]
+    pub max_hold_ms: Option<u64>,
+
+    /// allow-list (simboluri sau mints); goală = toți tokenii urmăriți din registry
+    #[serde(default)]
+    pub tokens: Vec<String>,
+    /// nu se vând niciodată (ex. mSOL/JitoSOL ținute intenționat); are prioritate față de `tokens`
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// cât din balanța disponibilă (peste rezervă) se vinde
+    #[serde(default = "default_unwind_sell_fraction")]
+    pub sell_fraction: f64,
+    /// override-uri per token, cheia = simbol sau mint
+    #[serde(default)]
+    pub per_token: HashMap<String, UnwindTokenCfg>,
+}
+
+fn default_unwind_sell_fraction() -> f64 { 0.995 }
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UnwindTokenCfg {
+    /// în locul lui `min_token_ui`
+    #[serde(default)]
+    pub min_ui: Option<f64>,
+    /// în locul lui `sell_fraction`
+    #[serde(default)]
+    pub sell_fraction: Option<f64>,
+    /// rezervă (UI) care rămâne mereu în wallet
+    #[serde(default)]
+    pub reserve_ui: Option<f64>,
 }
 
 impl Config {
+    /// Citește și validează config-ul. Warning-urile se afișează, erorile opresc încărcarea.
     pub fn load_from_file(path: &str) -> Result<Self> {
-        let txt = fs::read_to_string(Path::new(path))?;
-        let cfg: Self = serde_json::from_str(&txt)?;
+        let (cfg, report) = Self::parse_file(path)?;
+        for w in report.warnings() {
+            eprintln!("[CONFIG] {}", w);
+        }
+        let errors = report.errors();
+        if !errors.is_empty() {
+            return Err(ConfigError(errors).into());
+        }
         Ok(cfg)
     }
+
+    /// Parsează fișierul și rulează validarea, fără să afișeze nimic.
+    /// `Err` doar pentru I/O sau JSON care nu se poate deserializa.
+    pub fn parse_file(path: &str) -> Result<(Self, Report)> {
+        let txt = fs::read_to_string(Path::new(path))?;
+        let raw: serde_json::Value =
+            serde_json::from_str(&txt).map_err(|e| anyhow!("{}: invalid JSON: {}", path, e))?;
+        let cfg: Self = serde_json::from_str(&txt).map_err(|e| anyhow!("{}: {}", path, e))?;
+
+        let report = validate::validate(&cfg, &raw);
+        Ok((cfg, report))
+    }
 } 
\ No newline at end of file
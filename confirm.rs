@@ -0,0 +1,85 @@
+// src/confirm.rs
+use anyhow::{anyhow, Result};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::TransactionError;
+use std::time::Duration;
+
+use crate::rpc::RpcRotator;
+
+/// cât de des întrebăm getSignatureStatuses
+const POLL_MS: u64 = 1_500;
+/// câte blocuri rămâne valid un blockhash (dacă nu știm lastValidBlockHeight)
+const BLOCKHASH_VALID_BLOCKS: u64 = 150;
+/// după atâtea erori RPC consecutive renunțăm la urmărire
+const MAX_POLL_ERRORS: u32 = 10;
+
+/// Rezultatul final al unei tranzacții trimise.
+#[derive(Debug, Clone)]
+pub enum TxOutcome {
+    /// a aterizat și a reușit (commitment confirmed)
+    Landed { slot: u64 },
+    /// a aterizat, dar a eșuat on-chain (eroare de program, slippage etc.)
+    Failed { slot: u64, err: TransactionError },
+    /// blockhash-ul a expirat fără ca tx să apară
+    Expired,
+}
+
+impl TxOutcome {
+    pub fn tag(&self) -> &'static str {
+        match self {
+            TxOutcome::Landed { .. } => "LANDED",
+            TxOutcome::Failed { .. } => "FAILED",
+            TxOutcome::Expired => "EXPIRED",
+        }
+    }
+}
+
+/// Urmărește `sig` până ajunge la `confirmed` sau până trece `last_valid_block_height`.
+pub async fn wait_for_outcome(
+    rpcs: &RpcRotator,
+    sig: &Signature,
+    last_valid_block_height: Option<u64>,
+) -> Result<TxOutcome> {
+    let last_valid = match last_valid_block_height {
+        Some(h) => h,
+        None => rpcs.call(|c| async move { c.get_block_height().await }).await? + BLOCKHASH_VALID_BLOCKS,
+    };
+
+    let mut errors = 0u32;
+    loop {
+        let status = rpcs
+            .call(|c| async move { c.get_signature_statuses(std::slice::from_ref(sig)).await })
+            .await;
+
+        match status {
+            Ok(resp) => {
+                errors = 0;
+                if let Some(Some(st)) = resp.value.into_iter().next() {
+                    if let Some(err) = st.err {
+                        return Ok(TxOutcome::Failed { slot: st.slot, err });
+                    }
+                    if st.satisfies_commitment(CommitmentConfig::confirmed()) {
+                        return Ok(TxOutcome::Landed { slot: st.slot });
+                    }
+                }
+            }
+            Err(e) => {
+                errors += 1;
+                if errors >= MAX_POLL_ERRORS {
+                    return Err(anyhow!("status polling for {} gave up: {:#}", sig, e));
+                }
+            }
+        }
+
+        // încă sub `confirmed`: un tx doar `processed` (ex. pe un fork minoritar) poate fi
+        // abandonat, deci după lastValidBlockHeight îl considerăm expirat
+        if let Ok(h) = rpcs.call(|c| async move { c.get_block_height().await }).await {
+            if h > last_valid {
+                return Ok(TxOutcome::Expired);
+            }
+        }
+
+        tokio::time::sleep(Duration::from_millis(POLL_MS)).await;
+    }
+}
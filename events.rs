@@ -0,0 +1,298 @@
+// src/events.rs
+use anyhow::{anyhow, Result};
+use chrono::{Local, SecondsFormat};
+use serde::Serialize;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use solana_sdk::signature::Signature;
+use tracing_subscriber::Layer;
+
+use crate::config::LogsConfig;
+use crate::confirm::TxOutcome;
+use crate::metrics;
+
+/// target-ul tracing pe care îl ascultă sink-ul JSON-lines
+const TARGET: &str = "solpro::events";
+
+/// QuoteFetched e zgomotos; se scrie doar cu `logs.quotes`.
+static QUOTES: AtomicBool = AtomicBool::new(false);
+
+/// Evenimentele de trading, câte o linie JSON fiecare (`"event"` = numele variantei).
+/// Sumele sunt în unitățile raw ale mint-ului de intrare al ciclului.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event")]
+pub enum Event {
+    /// răspuns nou de la /quote (hit-urile din cache nu apar)
+    QuoteFetched {
+        input: String,
+        output: String,
+        amount_in: u64,
+        out_amount: u64,
+        impact_bps: f64,
+        amm: String,
+        slot: Option<u64>,
+    },
+    /// un ciclu cotat complet, înainte de decizie
+    CycleEvaluated {
+        path: &'static str,
+        key: String,
+        amount_in: u64,
+        back: u64,
+        costs: u64,
+        pnl: i128,
+        thresh: i128,
+    },
+    /// ciclu/swap abandonat; `reason` = cheia din statistici
+    DecisionSkipped {
+        path: &'static str,
+        key: String,
+        reason: &'static str,
+        detail: String,
+    },
+    TxSent {
+        path: &'static str,
+        key: String,
+        sig: String,
+    },
+    /// `outcome` = TxOutcome::tag (LANDED | FAILED | EXPIRED)
+    TxConfirmed {
+        path: &'static str,
+        key: String,
+        sig: String,
+        outcome: &'static str,
+        slot: Option<u64>,
+        err: Option<String>,
+    },
+    UnwindExecuted {
+        mint: String,
+        symbol: String,
+        amount_in: u64,
+        out_amount: u64,
+        sig: String,
+    },
+}
+
+impl Event {
+    /// TxConfirmed din rezultatul lui `confirm::wait_for_outcome`.
+    pub fn confirmed(path: &'static str, key: &str, sig: &Signature, outcome: &TxOutcome) -> Self {
+        let (slot, err) = match outcome {
+            TxOutcome::Landed { slot } => (Some(*slot), None),
+            TxOutcome::Failed { slot, err } => (Some(*slot), Some(err.to_string())),
+            TxOutcome::Expired => (None, None),
+        };
+        Event::TxConfirmed {
+            path,
+            key: key.to_string(),
+            sig: sig.to_string(),
+            outcome: outcome.tag(),
+            slot,
+            err,
+        }
+    }
+}
+
+/// Trimite evenimentul prin tracing (și în contoarele din `metrics`). Fără `logs.json_events`
+/// nu există subscriber și ieșim înainte de serializare.
+pub fn emit(ev: Event) {
+    metrics::on_event(&ev);
+    if !tracing::enabled!(target: TARGET, tracing::Level::INFO) {
+        return;
+    }
+    if matches!(ev, Event::QuoteFetched { .. }) && !QUOTES.load(Ordering::Relaxed) {
+        return;
+    }
+    match serde_json::to_string(&ev) {
+        Ok(json) => tracing::info!(target: TARGET, json = %json),
+        Err(e) => eprintln!("[LOGS] serialize {:?}: {}", ev, e),
+    }
+}
+
+/// Pornește sink-ul JSON-lines dacă e activat. Se apelează o singură dată, la start.
+pub fn init(cfg: Option<&LogsConfig>) -> Result<()> {
+    let Some(l) = cfg.filter(|l| l.json_events) else {
+        return Ok(());
+    };
+    fs::create_dir_all(&l.dir).map_err(|e| anyhow!("logs.dir {}: {}", l.dir, e))?;
+    QUOTES.store(l.quotes, Ordering::Relaxed);
+
+    let layer = JsonLinesLayer {
+        sink: Mutex::new(RotatingFile::new(l)),
+    };
+    tracing_subscriber::registry()
+        .with(layer)
+        .try_init()
+        .map_err(|e| anyhow!("tracing init: {}", e))?;
+
+    diag!(
+        "[LOGS] JSON events -> {}/events-*.jsonl (rotate={}, max {}MB, keep {}, quotes={})",
+        l.dir, l.rotate, l.max_file_mb, l.keep_files, l.quotes
+    );
+    Ok(())
+}
+
+/* ===================== Sink ===================== */
+
+struct JsonLinesLayer {
+    sink: Mutex<RotatingFile>,
+}
+
+impl<S: tracing::Subscriber> Layer<S> for JsonLinesLayer {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+        if event.metadata().target() != TARGET {
+            return;
+        }
+        let mut v = JsonField(None);
+        event.record(&mut v);
+        let Some(json) = v.0 else { return };
+        // {"event":...} -> {"ts":"...","event":...}
+        let Some(body) = json.strip_prefix('{') else { return };
+        let ts = Local::now().to_rfc3339_opts(SecondsFormat::Millis, false);
+        let line = format!("{{\"ts\":\"{}\",{}", ts, body);
+        if let Ok(mut sink) = self.sink.lock() {
+            sink.write_line(&line);
+        }
+    }
+}
+
+/// Extrage câmpul `json` (payload-ul serializat de `emit`).
+struct JsonField(Option<String>);
+
+impl Visit for JsonField {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "json" {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "json" {
+            self.0 = Some(format!("{:?}", value));
+        }
+    }
+}
+
+/// events-<perioadă>[.<n>].jsonl; fișier nou la schimbarea perioadei sau peste `max_bytes`.
+struct RotatingFile {
+    dir: PathBuf,
+    hourly: bool,
+    max_bytes: u64,
+    keep: usize,
+    period: String,
+    seq: u32,
+    file: Option<File>,
+    written: u64,
+}
+
+impl RotatingFile {
+    fn new(l: &LogsConfig) -> Self {
+        Self {
+            dir: PathBuf::from(&l.dir),
+            hourly: l.rotate == "hourly",
+            max_bytes: l.max_file_mb.saturating_mul(1024 * 1024),
+            keep: l.keep_files,
+            period: String::new(),
+            seq: 0,
+            file: None,
+            written: 0,
+        }
+    }
+
+    fn current_period(&self) -> String {
+        let fmt = if self.hourly { "%Y-%m-%d-%H" } else { "%Y-%m-%d" };
+        Local::now().format(fmt).to_string()
+    }
+
+    fn path(&self) -> PathBuf {
+        let name = if self.seq == 0 {
+            format!("events-{}.jsonl", self.period)
+        } else {
+            format!("events-{}.{}.jsonl", self.period, self.seq)
+        };
+        self.dir.join(name)
+    }
+
+    fn write_line(&mut self, line: &str) {
+        let len = line.len() as u64 + 1;
+        let period = self.current_period();
+        if period != self.period {
+            self.period = period;
+            self.seq = 0;
+            self.open();
+        } else if self.max_bytes > 0 && self.written + len > self.max_bytes {
+            self.seq += 1;
+            self.open();
+        } else if self.file.is_none() {
+            self.open();
+        }
+
+        let Some(f) = self.file.as_mut() else { return };
+        match writeln!(f, "{}", line) {
+            Ok(()) => self.written += len,
+            Err(e) => {
+                eprintln!("[LOGS] write {}: {}", self.path().display(), e);
+                self.file = None;
+            }
+        }
+    }
+
+    /// Deschide (append) fișierul curent; la restart în aceeași perioadă sare peste
+    /// bucățile deja pline.
+    fn open(&mut self) {
+        self.file = None;
+        loop {
+            let path = self.path();
+            let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            if self.max_bytes > 0 && size >= self.max_bytes {
+                self.seq += 1;
+                continue;
+            }
+            match OpenOptions::new().create(true).append(true).open(&path) {
+                Ok(f) => {
+                    self.file = Some(f);
+                    self.written = size;
+                }
+                Err(e) => eprintln!("[LOGS] open {}: {}", path.display(), e),
+            }
+            break;
+        }
+        self.prune();
+    }
+
+    /// Păstrează ultimele `keep` fișiere (numele se sortează cronologic).
+    fn prune(&self) {
+        if self.keep == 0 {
+            return;
+        }
+        let Ok(rd) = fs::read_dir(&self.dir) else { return };
+        let mut files: Vec<(String, u32, PathBuf)> = rd
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                let stem = name.strip_prefix("events-")?.strip_suffix(".jsonl")?.to_string();
+                // "2024-05-01.3" -> ("2024-05-01", 3), ca .10 să vină după .9
+                let (period, seq) = match stem.split_once('.') {
+                    Some((p, n)) => (p.to_string(), n.parse().ok()?),
+                    None => (stem, 0),
+                };
+                Some((period, seq, e.path()))
+            })
+            .collect();
+        if files.len() <= self.keep {
+            return;
+        }
+        files.sort();
+        let excess = files.len() - self.keep;
+        for (_, _, path) in files.into_iter().take(excess) {
+            if let Err(e) = fs::remove_file(&path) {
+                eprintln!("[LOGS] prune {}: {}", path.display(), e);
+            }
+        }
+    }
+}
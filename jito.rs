@@ -3,6 +3,7 @@ use base64::{engine::general_purpose::STANDARD as B64, Engine};
 use bincode;
 use reqwest::Client;
 use serde_json::json;
+use std::time::Instant;
 use solana_sdk::{
     hash::Hash,
     message::Message,
@@ -12,6 +13,11 @@ use solana_sdk::{
     transaction::{Transaction, VersionedTransaction},
 };
 
+use crate::metrics;
+
+/// Block Engine acceptă maxim 5 tranzacții într-un bundle.
+pub const MAX_BUNDLE_TXS: usize = 5;
+
 pub struct Jito {
     http: Client,
     pub block_engine: String,
@@ -33,25 +39,43 @@ impl Jito {
             .map_err(|e| anyhow!("invalid tip_account pubkey: {}", e))
     }
 
-    /// Trimite bundle (tip + swap) la Block Engine. Returnează id-ul bundle-ului.
-    pub async fn send_bundle(&self, tip_vtx: &VersionedTransaction, swap_vtx: &VersionedTransaction) -> Result<String> {
+    /// Trimite un bundle la Block Engine (JSON-RPC `sendBundle`, encoding base64).
+    /// Tranzacțiile se execută în ordine, toate-sau-nimic. Returnează id-ul bundle-ului.
+    pub async fn send_bundle(&self, txs: &[VersionedTransaction]) -> Result<String> {
+        if txs.is_empty() || txs.len() > MAX_BUNDLE_TXS {
+            return Err(anyhow!("[JITO] bundle invalid: {} tx (max {})", txs.len(), MAX_BUNDLE_TXS));
+        }
+
         // serialize + b64
-        let tip_b64  = B64.encode(bincode::serialize(tip_vtx)?);
-        let swap_b64 = B64.encode(bincode::serialize(swap_vtx)?);
+        let mut encoded = Vec::with_capacity(txs.len());
+        for tx in txs {
+            encoded.push(B64.encode(bincode::serialize(tx)?));
+        }
 
         let body = json!({
-            "transactions": [tip_b64, swap_b64]
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "sendBundle",
+            "params": [encoded, { "encoding": "base64" }]
         });
 
-        let resp = self.http.post(&self.block_engine).json(&body).send().await?;
+        let t0 = Instant::now();
+        let res = self.http.post(&self.block_engine).json(&body).send().await;
+        let resp = metrics::track_http("jito", "sendBundle", t0, res)?;
         let status = resp.status();
         if !status.is_success() {
             let txt = resp.text().await.unwrap_or_default();
             return Err(anyhow!("[JITO] HTTP {} {}", status, txt));
         }
-        // câmpurile pot varia; încercăm "id" sau un câmp text generic
+
         let v: serde_json::Value = resp.json().await.unwrap_or(serde_json::json!({}));
-        if let Some(id) = v.get("id").and_then(|x| x.as_str()) {
+        if let Some(err) = v.get("error") {
+            return Err(anyhow!("[JITO] sendBundle error: {}", err));
+        }
+        // "result" = bundle id; fallback pe "id" / tot răspunsul
+        if let Some(id) = v.get("result").and_then(|x| x.as_str()) {
+            Ok(id.to_string())
+        } else if let Some(id) = v.get("id").and_then(|x| x.as_str()) {
             Ok(id.to_string())
         } else {
             Ok(v.to_string())
@@ -80,9 +104,10 @@ pub fn legacy_to_v0(tx: &Transaction) -> Result<VersionedTransaction> {
     Ok(VersionedTransaction::from(tx.clone()))
 }
 
-/// Client Jito minimal (shim). Îl completăm ulterior.
+/// Client Jito folosit de bucla de bundles: ține config-ul de tip și reîncercări.
 pub struct JitoClient {
-    pub block_engine: String,
+    jito: Jito,
+    tip_pubkey: Pubkey,
     pub tip_account: String,
     pub default_tip_lamports: u64,
     pub max_bundle_retries: u32,
@@ -95,11 +120,24 @@ impl JitoClient {
         default_tip_lamports: u64,
         max_bundle_retries: u32,
     ) -> Result<Self> {
+        let jito = Jito::new(block_engine, tip_account.clone());
+        let tip_pubkey = jito.tip_pubkey()?;
         Ok(Self {
-            block_engine,
+            jito,
+            tip_pubkey,
             tip_account,
             default_tip_lamports,
             max_bundle_retries,
         })
     }
-}
\ No newline at end of file
+
+    /// Tx de tip către `tip_account`, semnată pe același blockhash ca restul bundle-ului.
+    pub fn build_tip_tx(&self, payer: &Keypair, recent_blockhash: Hash) -> Result<VersionedTransaction> {
+        build_tip_tx_v0(payer, &self.tip_pubkey, self.default_tip_lamports, recent_blockhash)
+    }
+
+    /// O singură încercare de trimitere; reîncercările (cu blockhash nou) le face apelantul.
+    pub async fn send_bundle(&self, txs: &[VersionedTransaction]) -> Result<String> {
+        self.jito.send_bundle(txs).await
+    }
+}
@@ -1,7 +1,107 @@
 // src/jupiter.rs
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use reqwest::{header::ACCEPT, Client};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use serde_with::{serde_as, DisplayFromStr};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::cache::{CacheStats, QuoteCache, QuoteKey};
+use crate::events::{self, Event};
+use crate::metrics;
+use crate::window::{Summary, Window};
+
+/// câte latențe /quote se păstrează pentru percentile
+const QUOTE_LATENCY_WINDOW: usize = 4096;
+
+/* ===================== Modele răspuns Jupiter v6 ===================== */
+
+/// Răspunsul de la `/quote`. Sumele vin ca string-uri în JSON.
+/// Câmpurile necunoscute sunt păstrate în `extra`, ca să trimitem quote-ul
+/// neschimbat înapoi la `/swap`.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteResponse {
+    pub input_mint: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub in_amount: u64,
+    pub output_mint: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub out_amount: u64,
+    /// minimul garantat după slippage (ExactIn)
+    #[serde_as(as = "DisplayFromStr")]
+    pub other_amount_threshold: u64,
+    #[serde(default)]
+    pub swap_mode: Option<String>,
+    #[serde(default)]
+    pub slippage_bps: u64,
+    /// fracție (ex: "0.0002" = 2 bps)
+    #[serde_as(as = "DisplayFromStr")]
+    pub price_impact_pct: f64,
+    pub route_plan: Vec<RoutePlanStep>,
+    #[serde(default)]
+    pub context_slot: Option<u64>,
+    #[serde(default)]
+    pub time_taken: Option<f64>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutePlanStep {
+    pub swap_info: SwapInfo,
+    pub percent: u8,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapInfo {
+    pub amm_key: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    pub input_mint: String,
+    pub output_mint: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub in_amount: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub out_amount: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub fee_amount: u64,
+    pub fee_mint: String,
+}
+
+/// Răspunsul de la `/swap`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapResponse {
+    /// tranzacția (VersionedTransaction) serializată base64
+    pub swap_transaction: String,
+    #[serde(default)]
+    pub last_valid_block_height: Option<u64>,
+    #[serde(default)]
+    pub prioritization_fee_lamports: Option<u64>,
+    #[serde(default)]
+    pub compute_unit_limit: Option<u32>,
+}
+
+impl QuoteResponse {
+    /// Eticheta AMM-ului din primul pas al rutei ("?" dacă lipsește).
+    pub fn amm_label(&self) -> &str {
+        self.route_plan
+            .first()
+            .and_then(|s| s.swap_info.label.as_deref())
+            .unwrap_or("?")
+    }
+
+    /// Impactul de preț în bps.
+    pub fn price_impact_bps(&self) -> f64 {
+        self.price_impact_pct * 10_000.0
+    }
+}
 
 #[derive(Clone)]
 pub struct JupiterClient {
@@ -9,10 +109,13 @@ pub struct JupiterClient {
     prefer_orca: bool,
     http: Client,
     slippage_bps: u64,
+    cache: Arc<QuoteCache>,
+    /// latența /quote reușite, în ms (doar fetch-urile reale, nu hit-urile din cache)
+    latency: Arc<Mutex<Window>>,
 }
 
 impl JupiterClient {
-    pub fn new(base: String, prefer_orca: bool, slippage_bps: u64) -> Self {
+    pub fn new(base: String, prefer_orca: bool, slippage_bps: u64, quote_cache_ttl_ms: u64) -> Self {
         let http = Client::builder()
             .gzip(true)
             .brotli(true)
@@ -25,16 +128,66 @@ impl JupiterClient {
             prefer_orca,
             http,
             slippage_bps,
+            cache: Arc::new(QuoteCache::new(quote_cache_ttl_ms)),
+            latency: Arc::new(Mutex::new(Window::new(QUOTE_LATENCY_WINDOW))),
         }
     }
 
+    pub fn cache(&self) -> &QuoteCache {
+        &self.cache
+    }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    /// Percentilele latenței /quote (ms) pe ultimele QUOTE_LATENCY_WINDOW fetch-uri.
+    pub fn quote_latency(&self) -> Option<Summary> {
+        self.latency.lock().unwrap().summary()
+    }
+
+    /// Quote prin cache: un hit proaspăt nu mai consumă din rate limit-ul Jupiter,
+    /// iar cererile identice concurente împart același request HTTP.
     pub async fn quote(
         &self,
         input_mint: &str,
         output_mint: &str,
         amount: u64,
         only_direct: Option<bool>,
-    ) -> Result<Value> {
+    ) -> Result<QuoteResponse> {
+        self.quote_with_slippage(input_mint, output_mint, amount, only_direct, None).await
+    }
+
+    /// Ca `quote`, cu slippage propriu (ex. override per pereche); None = cel global.
+    pub async fn quote_with_slippage(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+        only_direct: Option<bool>,
+        slippage_bps: Option<u64>,
+    ) -> Result<QuoteResponse> {
+        let slippage_bps = slippage_bps.unwrap_or(self.slippage_bps);
+        let key = QuoteKey {
+            input: input_mint.to_string(),
+            output: output_mint.to_string(),
+            amount,
+            only_direct,
+            slippage_bps,
+        };
+        self.cache
+            .get_or_fetch(key, || self.fetch_quote(input_mint, output_mint, amount, only_direct, slippage_bps))
+            .await
+    }
+
+    async fn fetch_quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+        only_direct: Option<bool>,
+        slippage_bps: u64,
+    ) -> Result<QuoteResponse> {
         let url = format!("{}/quote", self.base);
         let mut req = self
             .http
@@ -44,7 +197,7 @@ impl JupiterClient {
                 ("inputMint", input_mint),
                 ("outputMint", output_mint),
                 ("amount", &amount.to_string()),
-                ("slippageBps", &self.slippage_bps.to_string()),
+                ("slippageBps", &slippage_bps.to_string()),
             ]);
 
         if let Some(d) = only_direct {
@@ -55,17 +208,45 @@ impl JupiterClient {
             req = req.query(&[("preferDex", "Orca")]);
         }
 
-        let v = req.send().await?.error_for_status()?.json::<Value>().await?;
-        Ok(v)
+        let t0 = Instant::now();
+        let txt = metrics::track_http("jupiter", "quote", t0, req.send().await)?
+            .error_for_status()?
+            .text()
+            .await?;
+        self.latency.lock().unwrap().push(t0.elapsed().as_secs_f64() * 1000.0);
+        let q: QuoteResponse = serde_json::from_str(&txt)
+            .with_context(|| format!("malformed /quote response: {}", truncate(&txt, 300)))?;
+        if q.route_plan.is_empty() {
+            return Err(anyhow!("quote {}→{} without routePlan", input_mint, output_mint));
+        }
+        events::emit(Event::QuoteFetched {
+            input: q.input_mint.clone(),
+            output: q.output_mint.clone(),
+            amount_in: q.in_amount,
+            out_amount: q.out_amount,
+            impact_bps: q.price_impact_bps(),
+            amm: q.amm_label().to_string(),
+            slot: q.context_slot,
+        });
+        Ok(q)
     }
 
     /// Întoarce base64-ul tranzacției (câmpul "swapTransaction")
     pub async fn swap_tx(
         &self,
-        quote: &Value,
+        quote: &QuoteResponse,
         user_pubkey: &str,
         tip_lamports: u64,
     ) -> Result<String> {
+        Ok(self.swap(quote, user_pubkey, tip_lamports).await?.swap_transaction)
+    }
+
+    pub async fn swap(
+        &self,
+        quote: &QuoteResponse,
+        user_pubkey: &str,
+        tip_lamports: u64,
+    ) -> Result<SwapResponse> {
         let url = format!("{}/swap", self.base);
 
         let body = serde_json::json!({
@@ -77,23 +258,21 @@ impl JupiterClient {
             "prioritizationFeeLamports": tip_lamports
         });
 
-        let resp = self
-            .http
-            .post(&url)
-            .header(ACCEPT, "application/json")
-            .json(&body)
-            .send()
-            .await?
+        let t0 = Instant::now();
+        let res = self.http.post(&url).header(ACCEPT, "application/json").json(&body).send().await;
+        let txt = metrics::track_http("jupiter", "swap", t0, res)?
             .error_for_status()?
-            .json::<Value>()
+            .text()
             .await?;
 
-        if let Some(s) = resp.get("swapTransaction").and_then(|x| x.as_str()) {
-            Ok(s.to_string())
-        } else if let Some(s) = resp.as_str() {
-            Ok(s.to_string())
-        } else {
-            Err(anyhow!("Unexpected /swap response: {}", resp))
-        }
+        serde_json::from_str::<SwapResponse>(&txt)
+            .map_err(|e| anyhow!("Unexpected /swap response ({}): {}", e, truncate(&txt, 300)))
+    }
+}
+
+fn truncate(s: &str, max: usize) -> &str {
+    match s.char_indices().nth(max) {
+        Some((i, _)) => &s[..i],
+        None => s,
     }
-} 
\ No newline at end of file
+}
@@ -1,4 +1,4 @@
-use std::{num::NonZeroU32, sync::Arc, time::Duration};
+use std::{num::NonZeroU32, sync::Arc, time::{Duration, Instant}};
 use governor::{
     clock::DefaultClock,
     state::{InMemoryState, NotKeyed},
@@ -7,6 +7,8 @@ use governor::{
 use rand::{Rng, thread_rng};
 use tokio::time::sleep;
 
+use crate::metrics;
+
 #[derive(Clone)]
 pub struct Limiter {
     rl: Arc<RateLimiter<NotKeyed, InMemoryState, DefaultClock>>,
@@ -30,14 +32,16 @@ impl Limiter {
 
     /// Așteaptă până e voie să facem următoarea cerere + aplică jitter
     pub async fn wait(&self) {
+        let t0 = Instant::now();
         self.rl.until_ready().await;
 
         if self.jitter.as_millis() > 0 {
-            let mut rng = thread_rng();
-            let j = rng.gen_range(0..self.jitter.as_millis() as u64);
+            // ThreadRng nu e Send: nu-l ținem peste await (wait() rulează în task-uri spawn-uite)
+            let j = thread_rng().gen_range(0..self.jitter.as_millis() as u64);
             if j > 0 {
                 sleep(Duration::from_millis(j)).await;
             }
         }
+        metrics::observe("solpro_limiter_wait_seconds", &[], t0.elapsed().as_secs_f64());
     }
 } 
\ No newline at end of file
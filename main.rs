@@ -1,25 +1,52 @@
 // src/main.rs
+
+/// println! pentru mesajele de diagnostic; cu `--format json` merg pe stderr.
+macro_rules! diag {
+    ($($arg:tt)*) => {
+        if $crate::cli::json_stdout() {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
 mod config;
 mod rpc;
+mod cache;
 mod jupiter;
 mod resolver;
 mod arbitrage;
 mod limiter;
 mod accounts;
+mod amount;
+mod registry;
+mod confirm;
+mod pnl;
+mod simulate;
+mod reload;
+mod validate;
+mod cli;
 
 // +++ JITO & bundles
 mod jito;
 mod arbs;
+mod sizing;
+mod risk;
+mod ata;
+mod events;
+mod metrics;
+mod window;
 
 use colored::Colorize;
-use accounts::TOKENS;
 use limiter::Limiter;
 
 use resolver::Resolver;
+use registry::{TokenEntry, TokenRegistry};
 use arbitrage::Arbitrage;
 
 use base64::prelude::*;
-use solana_client::rpc_client::RpcClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_client::rpc_config::RpcSendTransactionConfig;
 use solana_client::rpc_request::TokenAccountsFilter;
 
@@ -28,15 +55,24 @@ use solana_sdk::message::VersionedMessage;
 use solana_sdk::transaction::VersionedTransaction;
 
 use anyhow::{anyhow, Result};
-use config::{Config, Pair};
+use config::{AutoUnwindCfg, CleanupConfig, Config, Fees, Pair};
 use jupiter::JupiterClient;
-use rpc::RpcRotator;
+use rpc::{EndpointHealth, RpcRotator};
+use reload::SharedConfig;
+use cli::{Cli, Command, OutputFormat};
+use validate::ConfigError;
+use confirm::TxOutcome;
+use pnl::{MarkPrice, PnlLedger, Position, TradeCtx};
+use amount::{Lamports, RawAmount, UiAmount};
+use risk::{Reject, TradePath};
+use ata::TokenAccount;
+use window::{Summary, Window};
+use events::Event;
 
-use solana_sdk::native_token::sol_to_lamports;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::signature::{Keypair, Signature, Signer};
 
-use std::{fs, path::Path, sync::Arc, time::Duration};
+use std::{fs, path::Path, sync::Arc, time::{Duration, Instant}};
 use std::str::FromStr;
 
 // +++ JITO & bundles
@@ -44,8 +80,10 @@ use crate::jito::JitoClient;
 use crate::arbs::run_bundles_once;
 
 // NEW: pentru rezumatul orar
-use std::collections::HashMap;
-use tokio::sync::Mutex;
+use cache::CacheStats;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Display;
+use tokio::sync::{watch, Mutex};
 use chrono::{Local, Datelike, Timelike};
 
 // ======================= Helpers existente =======================
@@ -75,66 +113,100 @@ fn looks_like_base64(s: &str) -> bool {
         && no_ws.len() >= 4
 }
 
-/// simbol (ex: "USDC") pentru un mint, dacă există în accounts::TOKENS
-fn symbol_for_mint(mint: &str) -> Option<&'static str> {
-    for (sym, m) in TOKENS.iter() {
-        if *m == mint {
-            return Some(*sym);
-        }
-    }
-    None
+/// simbol (ex: "USDC") pentru un mint, dacă e cunoscut în TokenRegistry
+fn symbol_for_mint(mint: &str) -> Option<String> {
+    registry::global().symbol_for_mint(mint)
 }
 
-/// Citește balanța SPL totală (în unități UI) pentru un mint dat.
-fn spl_balance_ui(client: &RpcClient, owner: &Pubkey, mint_str: &str) -> Result<(u64, f64)> {
+/// Citește balanța SPL totală (toate conturile wallet-ului) pentru un mint dat.
+async fn spl_balance(client: &RpcClient, owner: &Pubkey, mint_str: &str) -> Result<RawAmount> {
     let mint = mint_str.parse::<Pubkey>()?;
-    let accs = client.get_token_accounts_by_owner(owner, TokenAccountsFilter::Mint(mint))?;
+    let accs = client.get_token_accounts_by_owner(owner, TokenAccountsFilter::Mint(mint)).await?;
 
     let mut total_amount_raw: u128 = 0;
     let mut decimals: Option<u8> = None;
 
-    for keyed in accs {
-        let pk = Pubkey::from_str(&keyed.pubkey)?;
-        let bal = client.get_token_account_balance(&pk)?;
-        if let Ok(v) = bal.amount.parse::<u128>() {
-            total_amount_raw += v;
-        }
-        if decimals.is_none() {
-            decimals = bal.decimals.into();
-        }
+    // răspunsul e jsonParsed: balanța e deja în cont, fără getTokenAccountBalance per cont
+    for acc in accs.iter().filter_map(TokenAccount::parse) {
+        total_amount_raw += acc.amount as u128;
+        decimals.get_or_insert(acc.decimals);
     }
 
-    let d = decimals.unwrap_or(0) as u32;
-    let ui = if d == 0 {
-        total_amount_raw as f64
-    } else {
-        (total_amount_raw as f64) / 10f64.powi(d as i32)
-    };
-
     let raw_u64 = total_amount_raw.min(u64::MAX as u128) as u64;
-    Ok((raw_u64, ui))
+    Ok(RawAmount::new(raw_u64, mint_str, decimals.unwrap_or(0)))
+}
+
+/// Toate balanțele SPL ale wallet-ului dintr-un singur getTokenAccountsByOwner (jsonParsed),
+/// însumate pe mint (un mint poate avea mai multe conturi).
+async fn wallet_token_balances(client: &RpcClient, owner: &Pubkey) -> Result<HashMap<String, RawAmount>> {
+    let mut out: HashMap<String, RawAmount> = HashMap::new();
+    for acc in ata::list_token_accounts(client, owner).await? {
+        out.entry(acc.mint.clone())
+            .and_modify(|b| b.amount = b.amount.saturating_add(acc.amount))
+            .or_insert_with(|| RawAmount::new(acc.amount, &acc.mint, acc.decimals));
+    }
+    Ok(out)
 }
 
 // ======================= STATS + REPORTER (NEW) =======================
 
+/// câte PnL-uri estimate se păstrează per pereche pentru percentile
+const PAIR_PNL_WINDOW: usize = 1024;
+
+/// Cel mai profitabil ciclu estimat al unei perechi (poate fi și negativ).
+struct BestCycle {
+    pnl: i128,
+    amount_in: u64,
+    at: chrono::DateTime<Local>,
+}
+
+impl BestCycle {
+    fn bps(&self) -> f64 {
+        self.pnl as f64 * 10_000.0 / self.amount_in.max(1) as f64
+    }
+}
+
 #[derive(Default)]
 struct Stats {
     total_attempts: u64,
+    // execs = tx trimise care au aterizat cu succes (confirmed)
     total_execs: u64,
     total_skips: u64,
+    total_sent: u64,
+    total_failed: u64,
+    total_expired: u64,
+    // skip-uri de pereche pe motiv: balance, quote_error + motivele din risk (impact, no_output, profit);
+    // respingerile de unwind apar doar în `rejects`
+    skips: BTreeMap<&'static str, u64>,
     // pe pereche (folosim "SYM1→SYM2" dacă putem, altfel mints)
-    per_pair_attempts: HashMap<String, u64>,
-    per_pair_execs: HashMap<String, u64>,
+    per_pair_attempts: BTreeMap<String, u64>,
+    per_pair_execs: BTreeMap<String, u64>,
+    // PnL estimat (raw A) al fiecărui ciclu cotat complet și cel mai bun ciclu, pe pereche
+    pair_pnl: BTreeMap<String, Window>,
+    best_cycle: BTreeMap<String, BestCycle>,
     // auto-unwind execs pe token (SYM sau mint)
-    unwind_execs: HashMap<String, u64>,
+    unwind_execs: BTreeMap<String, u64>,
+    // snapshot din QuoteCache, actualizat de reporter înainte de render
+    quote_cache: CacheStats,
+    // snapshot din jup.quote_latency() (ms), la fel
+    quote_latency: Option<Summary>,
+    // snapshot din rpcs.health_snapshot(), la fel
+    rpc_health: Vec<EndpointHealth>,
+    // snapshot din risk::rejections() ("cale/motiv" -> număr), la fel
+    rejects: Vec<(String, u64)>,
+    // cleanup: conturi de token închise și rent-ul recuperat
+    closed_accounts: u64,
+    dust_burned: u64,
+    dust_swept: u64,
+    reclaimed_lamports: u64,
 }
 
 impl Stats {
     fn new() -> Self { Self::default() }
 
     fn pair_key(a_mint: &str, b_mint: &str) -> String {
-        let a = symbol_for_mint(a_mint).unwrap_or(a_mint);
-        let b = symbol_for_mint(b_mint).unwrap_or(b_mint);
+        let a = symbol_for_mint(a_mint).unwrap_or_else(|| a_mint.to_string());
+        let b = symbol_for_mint(b_mint).unwrap_or_else(|| b_mint.to_string());
         format!("{a}→{b}")
     }
 
@@ -150,37 +222,216 @@ impl Stats {
         *self.per_pair_execs.entry(k).or_insert(0) += 1;
     }
 
-    fn inc_skip(&mut self) {
+    fn inc_skip(&mut self, reason: &'static str) {
         self.total_skips += 1;
+        *self.skips.entry(reason).or_insert(0) += 1;
+    }
+
+    /// Un ciclu de pereche cotat complet (înainte de decizie).
+    fn record_cycle(&mut self, key: &str, amount_in: u64, pnl: i128) {
+        self.pair_pnl
+            .entry(key.to_string())
+            .or_insert_with(|| Window::new(PAIR_PNL_WINDOW))
+            .push(pnl as f64);
+        if self.best_cycle.get(key).map(|b| pnl > b.pnl).unwrap_or(true) {
+            self.best_cycle.insert(key.to_string(), BestCycle { pnl, amount_in, at: Local::now() });
+        }
+    }
+
+    fn inc_sent(&mut self) {
+        self.total_sent += 1;
+    }
+
+    fn inc_failed(&mut self) {
+        self.total_failed += 1;
+    }
+
+    fn inc_expired(&mut self) {
+        self.total_expired += 1;
+    }
+
+    fn add_cleanup(&mut self, r: &ata::CloseReport) {
+        self.closed_accounts += r.closed;
+        self.dust_burned += r.burned;
+        self.reclaimed_lamports += r.reclaimed_lamports;
+    }
+
+    fn inc_dust_swept(&mut self) {
+        self.dust_swept += 1;
     }
 
     fn inc_unwind_exec(&mut self, token_mint: &str) {
-        let k = symbol_for_mint(token_mint).unwrap_or(token_mint).to_string();
+        let k = symbol_for_mint(token_mint).unwrap_or_else(|| token_mint.to_string());
         *self.unwind_execs.entry(k).or_insert(0) += 1;
     }
 
     fn render(&self) -> String {
         let mut s = String::new();
         s.push_str("=== Hourly Summary ===\n");
-        s.push_str(&format!("Attempts: {}\nExecs: {}\nSkips: {}\n", self.total_attempts, self.total_execs, self.total_skips));
-        s.push_str("\n-- Pair attempts --\n");
-        for (k, v) in self.per_pair_attempts.iter() {
-            s.push_str(&format!("{k}: {v}\n"));
-        }
-        s.push_str("\n-- Pair execs --\n");
-        for (k, v) in self.per_pair_execs.iter() {
-            s.push_str(&format!("{k}: {v}\n"));
-        }
-        s.push_str("\n-- Auto-unwind execs by token --\n");
-        for (k, v) in self.unwind_execs.iter() {
-            s.push_str(&format!("{k}: {v}\n"));
-        }
+        push_table(&mut s, "Totals", [
+            ("attempts", self.total_attempts),
+            ("execs", self.total_execs),
+            ("skips", self.total_skips),
+            ("sent", self.total_sent),
+            ("failed on-chain", self.total_failed),
+            ("expired", self.total_expired),
+        ]);
+        push_table(&mut s, "Skips by reason", by_count(&self.skips));
+        push_table(&mut s, "Pair attempts", by_count(&self.per_pair_attempts));
+        push_table(&mut s, "Pair execs", by_count(&self.per_pair_execs));
+        push_table(
+            &mut s,
+            "Estimated cycle PnL by pair (raw A)",
+            self.pair_pnl.iter().filter_map(|(k, w)| {
+                let d = w.summary()?;
+                Some((k, format!(
+                    "n={} sum={:.0} min={:.0} p10={:.0} p50={:.0} p90={:.0} max={:.0}",
+                    d.count, d.sum, d.min, d.p10, d.p50, d.p90, d.max
+                )))
+            }),
+        );
+        push_table(
+            &mut s,
+            "Best cycle by pair",
+            self.best_cycle.iter().map(|(k, b)| {
+                (k, format!("pnl={} ({:.2}bp) in={} at {}", b.pnl, b.bps(), b.amount_in, b.at.format("%H:%M:%S")))
+            }),
+        );
+        push_table(&mut s, "Quote latency (ms)", self.quote_latency.map(|l| {
+            ("/quote", format!(
+                "n={} p50={:.0} p90={:.0} p99={:.0} max={:.0}",
+                l.count, l.p50, l.p90, l.p99, l.max
+            ))
+        }));
+        let now = Instant::now();
+        push_table(&mut s, "RPC endpoints", self.rpc_health.iter().map(|e| {
+            (metrics::endpoint_label(&e.url), format!(
+                "latency={} ok={} err={} consecutive_err={} slot={} cooldown={}",
+                e.latency_ms.map(|l| format!("{:.0}ms", l)).unwrap_or_else(|| "-".into()),
+                e.ok_count, e.err_count, e.consecutive_errors,
+                e.last_slot.map(|s| s.to_string()).unwrap_or_else(|| "-".into()),
+                e.cooldown_until.is_some_and(|t| t > now)
+            ))
+        }));
+        push_table(&mut s, "Auto-unwind execs by token", by_count(&self.unwind_execs));
+        push_table(&mut s, "Risk rejections (path/reason)", self.rejects.iter().map(|(k, v)| (k, v)));
+        push_table(&mut s, "Account cleanup", [
+            ("closed", self.closed_accounts.to_string()),
+            ("dust_burned", self.dust_burned.to_string()),
+            ("dust_swept", self.dust_swept.to_string()),
+            ("reclaimed", format!(
+                "{} lamports ({:.6} SOL)",
+                self.reclaimed_lamports, Lamports(self.reclaimed_lamports).as_sol()
+            )),
+        ]);
+        let c = &self.quote_cache;
+        push_table(&mut s, "Quote cache", [
+            ("hits", c.hits.to_string()),
+            ("misses", c.misses.to_string()),
+            ("coalesced", c.coalesced.to_string()),
+            ("evictions", c.evictions.to_string()),
+            ("entries", c.entries.to_string()),
+            ("hit_ratio", format!("{:.1}%", c.hit_ratio() * 100.0)),
+        ]);
         s
     }
+
+    /// Același rezumat ca `render`, pentru procesare automată (summary-*.json).
+    fn to_json(&self, ledger: &PnlLedger) -> serde_json::Value {
+        let c = &self.quote_cache;
+        serde_json::json!({
+            "at": Local::now().to_rfc3339(),
+            "totals": {
+                "attempts": self.total_attempts,
+                "execs": self.total_execs,
+                "skips": self.total_skips,
+                "sent": self.total_sent,
+                "failed": self.total_failed,
+                "expired": self.total_expired,
+            },
+            "skips": self.skips,
+            "pair_attempts": self.per_pair_attempts,
+            "pair_execs": self.per_pair_execs,
+            "pair_pnl": self.pair_pnl.iter().map(|(k, w)| (k.clone(), w.summary())).collect::<BTreeMap<_, _>>(),
+            "best_cycle": self.best_cycle.iter().map(|(k, b)| (k.clone(), serde_json::json!({
+                "pnl": json_i128(b.pnl),
+                "bps": b.bps(),
+                "amount_in": b.amount_in,
+                "at": b.at.to_rfc3339(),
+            }))).collect::<BTreeMap<_, _>>(),
+            "quote_latency_ms": self.quote_latency,
+            "unwind_execs": self.unwind_execs,
+            "rejects": self.rejects.iter().cloned().collect::<BTreeMap<_, _>>(),
+            "cleanup": {
+                "closed": self.closed_accounts,
+                "dust_burned": self.dust_burned,
+                "dust_swept": self.dust_swept,
+                "reclaimed_lamports": self.reclaimed_lamports,
+            },
+            "rpc_endpoints": self.rpc_health.iter().map(|e| serde_json::json!({
+                "endpoint": metrics::endpoint_label(&e.url),
+                "latency_ms": e.latency_ms,
+                "ok": e.ok_count,
+                "err": e.err_count,
+                "consecutive_err": e.consecutive_errors,
+                "last_slot": e.last_slot,
+                "cooldown": e.cooldown_until.is_some_and(|t| t > Instant::now()),
+            })).collect::<Vec<_>>(),
+            "quote_cache": {
+                "hits": c.hits,
+                "misses": c.misses,
+                "coalesced": c.coalesced,
+                "evictions": c.evictions,
+                "entries": c.entries,
+                "hit_ratio": c.hit_ratio(),
+            },
+            "pnl": ledger.snapshot().into_iter().map(|(k, e)| (k, serde_json::json!({
+                "base_mint": e.base_mint,
+                "trades": e.trades,
+                "failed": e.failed,
+                "estimated": json_i128(e.estimated),
+                "realized": json_i128(e.realized),
+                "slippage": json_i128(e.slippage()),
+                "fees_lamports": e.fees_lamports,
+                "tips_lamports": e.tips_lamports,
+            }))).collect::<BTreeMap<_, _>>(),
+        })
+    }
+}
+
+/// Secțiune "-- titlu --" cu valorile aliniate după cea mai lungă cheie.
+fn push_table<K: Display, V: Display>(s: &mut String, title: &str, rows: impl IntoIterator<Item = (K, V)>) {
+    let rows: Vec<(String, String)> = rows.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    s.push_str(&format!("\n-- {} --\n", title));
+    if rows.is_empty() {
+        s.push_str("(none)\n");
+        return;
+    }
+    let w = rows.iter().map(|(k, _)| k.chars().count()).max().unwrap_or(0);
+    for (k, v) in rows {
+        s.push_str(&format!("{:<w$}  {}\n", k, v, w = w));
+    }
+}
+
+/// Contoarele descrescător după valoare, apoi după cheie.
+fn by_count<K: Ord>(m: &BTreeMap<K, u64>) -> Vec<(&K, u64)> {
+    let mut v: Vec<(&K, u64)> = m.iter().map(|(k, n)| (k, *n)).collect();
+    v.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    v
+}
+
+/// i128 -> JSON fără panică pe valori în afara lui i64 (json! face unwrap).
+fn json_i128(v: i128) -> i64 {
+    v.clamp(i64::MIN as i128, i64::MAX as i128) as i64
 }
 
 // Task periodic: scrie fișier din oră în oră
-async fn spawn_hourly_reporter(stats: Arc<Mutex<Stats>>) {
+async fn spawn_hourly_reporter(
+    stats: Arc<Mutex<Stats>>,
+    jup: Arc<JupiterClient>,
+    rpcs: Arc<RpcRotator>,
+    ledger: Arc<PnlLedger>,
+) {
     tokio::spawn(async move {
         loop {
             tokio::time::sleep(Duration::from_secs(3600)).await;
@@ -191,19 +442,144 @@ async fn spawn_hourly_reporter(stats: Arc<Mutex<Stats>>) {
                 eprintln!("[REPORT] create_dir_all logs failed: {}", e);
                 continue;
             }
-            let fname = format!("summary-{}{:02}{:02}-{:02}.txt",
+            let stem = format!("summary-{}{:02}{:02}-{:02}",
                                 now.year(), now.month(), now.day(), now.hour());
-            let path = dir.join(fname);
 
-            let snapshot = {
-                let st = stats.lock().await;
-                st.render()
+            let (text, json) = {
+                let mut st = stats.lock().await;
+                st.quote_cache = jup.cache_stats();
+                st.quote_latency = jup.quote_latency();
+                st.rpc_health = rpcs.health_snapshot();
+                st.rejects = risk::rejections();
+                (st.render() + &ledger.render(), st.to_json(&ledger))
+            };
+            let json = serde_json::to_string_pretty(&json).unwrap_or_default();
+
+            for (path, body) in [(dir.join(format!("{stem}.txt")), text), (dir.join(format!("{stem}.json")), json)] {
+                if let Err(e) = fs::write(&path, body) {
+                    eprintln!("[REPORT] write failed {}: {}", path.display(), e);
+                } else {
+                    diag!("[REPORT] wrote {}", path.display());
+                }
+            }
+        }
+    });
+}
+
+// Task periodic: balanțele wallet-ului (UI) pentru /metrics; tokenii dispăruți (cont închis) trec pe 0
+fn spawn_balance_metrics(rpcs: Arc<RpcRotator>, owner: Pubkey, every_ms: u64) {
+    tokio::spawn(async move {
+        let mut seen: HashSet<String> = HashSet::new();
+        loop {
+            match rpcs.call(|c| async move { c.get_balance(&owner).await }).await {
+                Ok(l) => metrics::set("solpro_wallet_balance", &[("token", "SOL"), ("mint", "native")], Lamports(l).as_sol()),
+                Err(e) => eprintln!("[METRICS] SOL balance failed: {:#}", e),
+            }
+            match rpcs.call(|c| async move { wallet_token_balances(&c, &owner).await }).await {
+                Ok(balances) => {
+                    for mint in seen.iter().filter(|m| !balances.contains_key(*m)) {
+                        let sym = symbol_for_mint(mint).unwrap_or_else(|| mint.clone());
+                        metrics::set("solpro_wallet_balance", &[("token", sym.as_str()), ("mint", mint.as_str())], 0.0);
+                    }
+                    for (mint, b) in &balances {
+                        let sym = symbol_for_mint(mint).unwrap_or_else(|| mint.clone());
+                        metrics::set("solpro_wallet_balance", &[("token", sym.as_str()), ("mint", mint.as_str())], b.ui().0);
+                    }
+                    seen.extend(balances.into_keys());
+                }
+                Err(e) => eprintln!("[METRICS] token balances failed: {:#}", e),
+            }
+            tokio::time::sleep(Duration::from_millis(every_ms)).await;
+        }
+    });
+}
+
+// Task periodic: scoate quote-urile expirate din cache
+fn spawn_quote_cache_gc(jup: Arc<JupiterClient>, every_ms: u64) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_millis(every_ms.max(1_000))).await;
+            jup.cache().gc();
+        }
+    });
+}
+
+// ======================= CONFIRMARE =======================
+
+/// Ce contor se incrementează când tx-ul aterizează.
+enum ExecKind {
+    Pair { input_mint: String, output_mint: String },
+    Unwind { input_mint: String, amount_in: u64, out_amount: u64 },
+}
+
+impl ExecKind {
+    fn path(&self) -> TradePath {
+        match self {
+            ExecKind::Pair { .. } => TradePath::Pair,
+            ExecKind::Unwind { .. } => TradePath::Unwind,
+        }
+    }
+}
+
+/// Urmărește tx-ul în fundal și actualizează Stats doar după rezultatul final;
+/// dacă avem `pnl`, trece și PnL-ul realizat (din balanțele on-chain) în ledger.
+fn spawn_confirm_and_record(
+    rpcs: Arc<RpcRotator>,
+    stats: Arc<Mutex<Stats>>,
+    sig: Signature,
+    last_valid_block_height: Option<u64>,
+    kind: ExecKind,
+    pnl: Option<(Arc<PnlLedger>, Pubkey, TradeCtx)>,
+) {
+    tokio::spawn(async move {
+        let outcome = confirm::wait_for_outcome(&rpcs, &sig, last_valid_block_height).await;
+
+        if let Ok(o) = &outcome {
+            let key = pnl.as_ref().map(|(_, _, ctx)| ctx.key.as_str()).unwrap_or("");
+            events::emit(Event::confirmed(kind.path().as_str(), key, &sig, o));
+        }
+
+        if let Some((ledger, owner, ctx)) = &pnl {
+            let landed = match &outcome {
+                Ok(TxOutcome::Landed { .. }) => Some(true),
+                Ok(TxOutcome::Failed { .. }) => Some(false),
+                _ => None,
             };
+            if let Some(landed) = landed {
+                if let Err(e) = ledger.record(&rpcs, owner, ctx, &[sig], landed).await {
+                    eprintln!("[PNL] {} sig={} realized PnL unavailable: {:#}", ctx.key, sig, e);
+                }
+            }
+        }
 
-            if let Err(e) = fs::write(&path, snapshot) {
-                eprintln!("[REPORT] write failed {}: {}", path.display(), e);
-            } else {
-                println!("[REPORT] wrote {}", path.display());
+        let mut st = stats.lock().await;
+        match outcome {
+            Ok(TxOutcome::Landed { slot }) => {
+                diag!("{} sig={} slot={}", "[EXECUTED]".green().bold(), sig, slot);
+                match &kind {
+                    ExecKind::Pair { input_mint, output_mint } => st.inc_exec(input_mint, output_mint),
+                    ExecKind::Unwind { input_mint, amount_in, out_amount } => {
+                        st.inc_unwind_exec(input_mint);
+                        events::emit(Event::UnwindExecuted {
+                            mint: input_mint.clone(),
+                            symbol: symbol_for_mint(input_mint).unwrap_or_default(),
+                            amount_in: *amount_in,
+                            out_amount: *out_amount,
+                            sig: sig.to_string(),
+                        });
+                    }
+                }
+            }
+            Ok(TxOutcome::Failed { slot, err }) => {
+                diag!("{} sig={} slot={} err={}", "[FAILED ON-CHAIN]".red().bold(), sig, slot, err);
+                st.inc_failed();
+            }
+            Ok(TxOutcome::Expired) => {
+                diag!("{} sig={} (blockhash expirat)", "[EXPIRED]".yellow(), sig);
+                st.inc_expired();
+            }
+            Err(e) => {
+                eprintln!("[CONFIRM] sig={} status necunoscut: {:#}", sig, e);
             }
         }
     });
@@ -211,55 +587,77 @@ async fn spawn_hourly_reporter(stats: Arc<Mutex<Stats>>) {
 
 // ======================= SWAP single-leg (auto-unwind) =======================
 
+/// Cost basis-ul unei vânzări: vindem doar dacă out >= `min_out`; `basis_per_unit` evaluează
+/// tokenul vândut în PnL (lipsă = cursul din quote).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SellGuard {
+    min_out: u64,
+    basis_per_unit: Option<f64>,
+}
+
+impl SellGuard {
+    /// vânzare forțată, fără cost basis (comanda `unwind`, sweep-ul de dust)
+    const FORCED: SellGuard = SellGuard { min_out: 0, basis_per_unit: None };
+}
+
+/// Unde ajunge rezultatul unui trade: contoarele raportului orar și ledger-ul de PnL.
+#[derive(Clone)]
+struct Reporting {
+    stats: Arc<Mutex<Stats>>,
+    ledger: Arc<PnlLedger>,
+}
+
+impl Reporting {
+    fn new() -> Self {
+        Self { stats: Arc::new(Mutex::new(Stats::new())), ledger: Arc::new(PnlLedger::new()) }
+    }
+}
+
+/// Vinde `amount_raw` din `input_mint` în `output_mint` (impact, priority fee și dry_run din config).
 async fn swap_single_leg(
+    cfg: &Config,
     jup: &JupiterClient,
-    client: &RpcClient,
+    rpcs: &Arc<RpcRotator>,
     kp: &Keypair,
     input_mint: &str,
     output_mint: &str,
     amount_raw: u64,
-    max_price_impact: f64,
-    tip_lamports: u64,
-    dry_run: bool,
-    // NEW: raportare
-    stats: &Arc<Mutex<Stats>>,
+    guard: SellGuard,
+    rep: &Reporting,
 ) -> Result<Option<String>> {
+    let SellGuard { min_out, basis_per_unit } = guard;
+    let Reporting { stats, ledger } = rep;
+    let max_impact_bps = risk::max_impact_bps(cfg, TradePath::Unwind, None);
     let quote = jup.quote(input_mint, output_mint, amount_raw, Some(false)).await?;
 
-    let out_u: u64 = quote
-        .get("outAmount").and_then(|x| x.as_str())
-        .and_then(|s| s.parse::<u64>().ok())
-        .unwrap_or(0);
-
-    let impact: f64 = quote
-        .get("priceImpactPct").and_then(|x| x.as_str())
-        .and_then(|s| s.parse::<f64>().ok())
-        .unwrap_or(0.0);
-
-    let amm_label = quote
-        .get("routePlan").and_then(|rp| rp.get(0))
-        .and_then(|r0| r0.get("swapInfo"))
-        .and_then(|si| si.get("label"))
-        .and_then(|l| l.as_str())
-        .unwrap_or("?");
+    let out_u: u64 = quote.out_amount;
+    let impact: f64 = quote.price_impact_pct;
+    let amm_label = quote.amm_label();
 
-    println!(
+    diag!(
         "[UNWIND QUOTE] {}→{} amt={} out={} amm={} impact={}",
         input_mint, output_mint, amount_raw, out_u, amm_label, impact
     );
 
-    if out_u == 0 || impact > max_price_impact {
-        println!("[UNWIND] skip: out=0 sau impact prea mare ({impact})");
+    if let Err(r) = risk::check_leg(&quote, 1, max_impact_bps) {
+        diag!("[UNWIND] skip: {}", r);
+        risk::record(TradePath::Unwind, input_mint, &r);
+        return Ok(None);
+    }
+    if out_u < min_out {
+        let r = Reject::MinOut { out: out_u, min_out };
+        diag!("[UNWIND] skip: {} (cost basis + fee + marjă)", r);
+        risk::record(TradePath::Unwind, input_mint, &r);
         return Ok(None);
     }
 
-    if dry_run {
-        println!("[UNWIND] DRY-RUN: ar executa acum.");
+    if cfg.dry_run {
+        diag!("[UNWIND] DRY-RUN: ar executa acum.");
         return Ok(None);
     }
 
-    let swap_b64: String = jup.swap_tx(&quote, &kp.pubkey().to_string(), tip_lamports).await?;
-    let tx_bytes = BASE64_STANDARD.decode(&swap_b64)?;
+    let swap = jup.swap(&quote, &kp.pubkey().to_string(), cfg.fees.priority_fee_lamports).await?;
+    let tx_bytes = BASE64_STANDARD.decode(&swap.swap_transaction)?;
     let mut vtx: VersionedTransaction = bincode::deserialize(&tx_bytes)?;
 
     let msg: &VersionedMessage = &vtx.message;
@@ -271,194 +669,810 @@ async fn swap_single_leg(
     }
 
     // (simulate a rămas doar pe rutele clasice; aici trimitem direct)
-    let sig = client.send_transaction_with_config(
-        &vtx,
-        RpcSendTransactionConfig {
-            skip_preflight: true,
-            max_retries: Some(1),
-            preflight_commitment: Some(CommitmentConfig::confirmed().commitment),
-            ..Default::default()
-        },
-    )?;
-    println!("[UNWIND EXECUTED] sig={}", sig);
+    let vtx = &vtx;
+    let sig = rpcs.call_send(|c| async move {
+        c.send_transaction_with_config(
+            vtx,
+            RpcSendTransactionConfig {
+                skip_preflight: true,
+                max_retries: Some(1),
+                preflight_commitment: Some(CommitmentConfig::confirmed().commitment),
+                ..Default::default()
+            },
+        ).await
+    }).await?;
+    diag!("[UNWIND SENT] sig={}", sig);
+    events::emit(Event::TxSent { path: TradePath::Unwind.as_str(), key: input_mint.to_string(), sig: sig.to_string() });
+
+    // PnL față de cost basis: tokenul vândut se evaluează la costul lui mediu
+    // (fără basis, la cursul din quote -> rămân doar fee-ul și slippage-ul)
+    let per_unit = basis_per_unit.unwrap_or(out_u as f64 / amount_raw.max(1) as f64);
+    let pnl_ctx = TradeCtx {
+        key: format!("unwind {}", symbol_for_mint(input_mint).unwrap_or_else(|| input_mint.to_string())),
+        base_mint: output_mint.to_string(),
+        estimated: out_u as i128 - (amount_raw as f64 * per_unit).round() as i128,
+        tip_lamports: 0,
+        // base-ul de unwind e SOL în config; pentru alt base nu avem curs și fee-ul nu se scade
+        sol_rate: None,
+        mark_to: Some(MarkPrice { mint: input_mint.to_string(), base_per_unit: per_unit }),
+    };
 
-    // contor
-    {
-        let mut st = stats.lock().await;
-        st.inc_unwind_exec(input_mint);
-    }
+    // contor: trimis acum, exec doar după confirmare
+    stats.lock().await.inc_sent();
+    spawn_confirm_and_record(
+        rpcs.clone(),
+        stats.clone(),
+        sig,
+        swap.last_valid_block_height,
+        ExecKind::Unwind { input_mint: input_mint.to_string(), amount_in: amount_raw, out_amount: out_u },
+        Some((ledger.clone(), kp.pubkey(), pnl_ctx)),
+    );
 
     Ok(Some(sig.to_string()))
 }
 
 // ======================= AUTO-UNWIND loop =======================
 
+/// cât așteptăm între verificări când auto-unwind e oprit (poate fi pornit prin reload)
+const AUTO_UNWIND_IDLE_MS: u64 = 5_000;
+
+/// Ce face auto-unwind cu un token ținut.
+enum UnwindPlan {
+    /// nu vindem încă (motivul e doar pentru log)
+    Hold(&'static str),
+    /// vindem dacă quote-ul dă cel puțin `min_out` în base
+    Sell(SellGuard),
+}
+
+/// `always` (sau `max_hold_ms` depășit) vinde oricum; `pnl_gt_0` / `bps` vând doar peste
+/// costul de achiziție + fee + marjă. Fără cost basis (ex. tokeni cumpărați înainte de
+/// pornire), modurile pe profit așteaptă până la `max_hold_ms`.
+fn unwind_plan(au: &AutoUnwindCfg, fees: &Fees, pos: Option<&Position>, held_for: Duration, sell_raw: u64) -> UnwindPlan {
+    let pos = pos.filter(|p| p.base_mint == au.base_mint);
+    let basis_per_unit = pos.map(|p| p.cost_per_unit());
+    let expired = au.max_hold_ms.is_some_and(|ms| held_for >= Duration::from_millis(ms));
+
+    if au.mode == "always" || expired {
+        return UnwindPlan::Sell(SellGuard { min_out: 0, basis_per_unit });
+    }
+    let Some(p) = pos else {
+        return UnwindPlan::Hold("no cost basis");
+    };
+
+    let cost = p.cost_of(sell_raw);
+    // fee-ul vânzării se plătește în SOL; îl adunăm doar dacă baza e SOL
+    let fee = if au.base_mint == accounts::WSOL_MINT {
+        fees.lamports_per_signature + fees.priority_fee_lamports
+    } else {
+        0
+    };
+    let margin = match au.mode.as_str() {
+        "bps" => ((cost as u128) * (au.min_profit_bps as u128) / 10_000u128) as u64,
+        // pnl_gt_0: strict peste cost
+        _ => 1,
+    };
+    UnwindPlan::Sell(SellGuard { min_out: cost.saturating_add(fee).saturating_add(margin.max(1)), basis_per_unit })
+}
+
+/// Regulile efective pentru un token (override din `per_token` > valorile globale).
+struct UnwindRule {
+    min_ui: f64,
+    sell_fraction: f64,
+    reserve_ui: f64,
+}
+
+fn unwind_rule(au: &AutoUnwindCfg, sym: &str, mint: &str) -> UnwindRule {
+    let t = au.per_token.get(mint).or_else(|| au.per_token.get(sym)).cloned().unwrap_or_default();
+    UnwindRule {
+        min_ui: t.min_ui.unwrap_or(au.min_token_ui).max(0.0),
+        sell_fraction: t.sell_fraction.unwrap_or(au.sell_fraction).clamp(0.0, 1.0),
+        reserve_ui: t.reserve_ui.unwrap_or(0.0).max(0.0),
+    }
+}
+
+/// (simbol, mint) candidați la unwind: `tokens` (sau, dacă e goală, toți tokenii urmăriți
+/// din registry) minus `deny` și baza.
+fn unwind_targets(au: &AutoUnwindCfg) -> Vec<(String, String)> {
+    let reg = registry::global();
+    let resolve = |list: &[String]| -> Vec<TokenEntry> {
+        list.iter()
+            .filter_map(|s| match reg.resolve(s) {
+                Ok(t) => Some(t),
+                Err(e) => {
+                    eprintln!("[AUTO-UNWIND] {:#}", e);
+                    None
+                }
+            })
+            .collect()
+    };
+    let deny: HashSet<String> = resolve(&au.deny).into_iter().map(|t| t.mint).collect();
+    let allow = if au.tokens.is_empty() { reg.tracked() } else { resolve(&au.tokens) };
+    allow
+        .into_iter()
+        .filter(|t| t.mint != au.base_mint && !deny.contains(&t.mint))
+        .map(|t| (t.symbol, t.mint))
+        .collect()
+}
+
 async fn auto_unwind_loop(
-    cfg: Arc<Config>,
+    shared: Arc<SharedConfig>,
     jup: Arc<JupiterClient>,
     rpcs: Arc<RpcRotator>,
     kp: Arc<Keypair>,
-    stats: Arc<Mutex<Stats>>, // NEW
+    rep: Reporting,
 ) {
-    let Some(au) = cfg.auto_unwind.clone() else {
-        return;
-    };
-    if !au.enabled {
-        return;
+    let owner = kp.pubkey();
+    let mut active = false;
+    // de când vedem fiecare token în wallet (pentru max_hold_ms fără cost basis)
+    let mut first_seen: HashMap<String, Instant> = HashMap::new();
+
+    loop {
+        // config-ul se poate schimba între treceri (hot-reload)
+        let cfg = shared.get();
+        let au = match cfg.auto_unwind.as_ref() {
+            Some(au) if au.enabled => au.clone(),
+            _ => {
+                if active {
+                    diag!("[AUTO-UNWIND] disabled");
+                    active = false;
+                }
+                tokio::time::sleep(Duration::from_millis(AUTO_UNWIND_IDLE_MS)).await;
+                continue;
+            }
+        };
+
+        let base_mint = au.base_mint.clone();
+        let check_ms = au.check_every_ms;
+
+        if !active {
+            diag!("[AUTO-UNWIND] enabled: base={} min_ui={} every={}ms mode={} min_profit_bps={} max_hold_ms={:?} allow={:?} deny={:?}",
+                base_mint, au.min_token_ui, check_ms, au.mode, au.min_profit_bps, au.max_hold_ms, au.tokens, au.deny);
+            active = true;
+        }
+
+        // toate balanțele dintr-un singur apel, apoi doar tokenii configurați
+        let balances = match rpcs.call(|c| async move { wallet_token_balances(&c, &owner).await }).await {
+            Ok(b) => b,
+            Err(e) => {
+                eprintln!("[AUTO-UNWIND] balance read failed: {:#}", e);
+                tokio::time::sleep(Duration::from_millis(check_ms)).await;
+                continue;
+            }
+        };
+
+        for (sym, mint) in unwind_targets(&au) {
+            let rule = unwind_rule(&au, &sym, &mint);
+            let Some(balance) = balances.get(&mint).filter(|b| b.ui() >= UiAmount(rule.min_ui)) else {
+                first_seen.remove(&mint);
+                continue;
+            };
+            // rezerva nu se vinde niciodată; din rest se vinde `sell_fraction`
+            let reserve = UiAmount(rule.reserve_ui).to_raw(balance.decimals).unwrap_or(u64::MAX);
+            let available = RawAmount::new(balance.amount.saturating_sub(reserve), &mint, balance.decimals);
+            let sell_raw = available.fraction(rule.sell_fraction).amount;
+            if sell_raw == 0 { continue; }
+
+            let pos = rep.ledger.position(&mint);
+            let since = pos
+                .as_ref()
+                .map(|p| p.opened)
+                .unwrap_or_else(|| *first_seen.entry(mint.clone()).or_insert_with(Instant::now));
+            let guard = match unwind_plan(&au, &cfg.fees, pos.as_ref(), since.elapsed(), sell_raw) {
+                UnwindPlan::Hold(why) => {
+                    diag!("[AUTO-UNWIND] {} hold: {} (held {}s)", sym, why, since.elapsed().as_secs());
+                    continue;
+                }
+                UnwindPlan::Sell(guard) => guard,
+            };
+
+            diag!("[AUTO-UNWIND] {} ({}) balance_ui={:.9} reserve_ui={} -> selling_raw={} min_out={} basis={:?}",
+                     sym, mint, balance.ui().0, rule.reserve_ui, sell_raw, guard.min_out, guard.basis_per_unit);
+
+            let _ = swap_single_leg(&cfg, &jup, &rpcs, &kp, &mint, &base_mint, sell_raw, guard, &rep).await;
+        }
+
+        tokio::time::sleep(Duration::from_millis(check_ms)).await;
     }
+}
 
-    let base_mint = au.base_mint.clone();
-    let check_ms = au.check_every_ms;
-    let min_ui = au.min_token_ui.max(0.0);
-    let max_impact = 0.003_f64; // 0.3% toleranță implicită la unwind
+// ======================= CLEANUP conturi de token =======================
 
-    println!("[AUTO-UNWIND] enabled: base={} min_ui={} every={}ms mode={}",
-        base_mint, min_ui, check_ms, au.mode);
+/// cât așteptăm între verificări când cleanup e oprit (poate fi pornit prin reload)
+const CLEANUP_IDLE_MS: u64 = 60_000;
 
+/// `limiter` urmărește limiter-ul buclei principale (reconstruit la reload).
+async fn account_cleanup_loop(
+    shared: Arc<SharedConfig>,
+    jup: Arc<JupiterClient>,
+    rpcs: Arc<RpcRotator>,
+    kp: Arc<Keypair>,
+    limiter: watch::Receiver<Arc<Limiter>>,
+    rep: Reporting,
+) {
     loop {
-        let client = rpcs.client();
-        for (sym, mint) in TOKENS.iter() {
-            if mint == &base_mint { continue; }
-
-            match spl_balance_ui(&client, &kp.pubkey(), mint) {
-                Ok((amount_raw, amount_ui)) => {
-                    if amount_ui < min_ui {
-                        continue;
-                    }
-                    let sell_raw = ((amount_raw as f64) * 0.995) as u64;
-                    if sell_raw == 0 { continue; }
-
-                    println!("[AUTO-UNWIND] {} ({}) balance_ui={:.9} -> selling_raw={}",
-                             sym, mint, amount_ui, sell_raw);
-
-                    let _ = swap_single_leg(
-                        &jup,
-                        &client,
-                        &kp,
-                        mint,
-                        &base_mint,
-                        sell_raw,
-                        max_impact,
-                        cfg.fees.priority_fee_lamports,
-                        cfg.dry_run,
-                        &stats,
-                    ).await;
-                }
-                Err(e) => {
-                    eprintln!("[AUTO-UNWIND] balance read failed for {} ({}): {}", sym, mint, e);
-                }
+        let cfg = shared.get();
+        let Some(cl) = cfg.cleanup.clone().filter(|c| c.enabled) else {
+            tokio::time::sleep(Duration::from_millis(CLEANUP_IDLE_MS)).await;
+            continue;
+        };
+
+        let lim = limiter.borrow().clone();
+        if let Err(e) = cleanup_pass(&cfg, &cl, &jup, &rpcs, &kp, &lim, &rep).await {
+            eprintln!("[CLEANUP] pass failed: {:#}", e);
+        }
+        tokio::time::sleep(Duration::from_millis(cl.every_ms)).await;
+    }
+}
+
+/// O trecere: conturile goale (și wSOL rămas) se închid; dust-ul (valoare sub `dust_max_lamports`)
+/// se vinde dacă merită swap-ul, altfel se arde și contul se închide. Contul golit prin swap
+/// se închide la trecerea următoare.
+async fn cleanup_pass(
+    cfg: &Config,
+    cl: &CleanupConfig,
+    jup: &JupiterClient,
+    rpcs: &Arc<RpcRotator>,
+    kp: &Arc<Keypair>,
+    limiter: &Limiter,
+    rep: &Reporting,
+) -> Result<()> {
+    let owner = kp.pubkey();
+    let token_accounts = rpcs.call(|c| async move { ata::list_token_accounts(&c, &owner).await }).await?;
+
+    // ATA-urile folosite de bot și tokenii ținuți intenționat nu se ating
+    let mut keep: HashSet<String> = HashSet::new();
+    if !cl.close_configured {
+        keep.extend(ata::setup_mints(cfg));
+    }
+    if let Some(au) = &cfg.auto_unwind {
+        keep.extend(au.deny.iter().filter_map(|s| registry::global().resolve(s).ok()).map(|t| t.mint));
+    }
+
+    let mut to_close: Vec<TokenAccount> = Vec::new();
+    for acc in token_accounts {
+        if keep.contains(&acc.mint) {
+            continue;
+        }
+        if acc.amount == 0 || acc.mint == accounts::WSOL_MINT {
+            to_close.push(acc);
+            continue;
+        }
+
+        // valoarea soldului în SOL, la quote
+        limiter.wait().await;
+        let value = match jup.quote(&acc.mint, accounts::WSOL_MINT, acc.amount, None).await {
+            Ok(q) => q.out_amount,
+            Err(e) => {
+                diag!("[CLEANUP] {} ({}) no SOL route, keep: {:#}", acc.mint, acc.amount, e);
+                continue;
             }
+        };
+        if value >= cl.dust_max_lamports {
+            continue;
+        }
+        let sym = symbol_for_mint(&acc.mint).unwrap_or_else(|| acc.mint.clone());
+        if value < cl.sweep_min_lamports {
+            diag!("[CLEANUP] {} dust amount={} value={} -> burn + close", sym, acc.amount, value);
+            to_close.push(acc);
+            continue;
         }
 
-        tokio::time::sleep(Duration::from_millis(check_ms)).await;
+        diag!("[CLEANUP] {} dust amount={} value={} -> sweep to SOL", sym, acc.amount, value);
+        let swept =
+            swap_single_leg(cfg, jup, rpcs, kp, &acc.mint, accounts::WSOL_MINT, acc.amount, SellGuard::FORCED, rep)
+                .await;
+        if let Ok(Some(_)) = swept {
+            rep.stats.lock().await.inc_dust_swept();
+        }
+    }
+
+    if to_close.is_empty() {
+        return Ok(());
+    }
+    let rent: u64 = to_close.iter().map(|a| a.rent_lamports()).sum();
+    if cfg.dry_run {
+        diag!("[CLEANUP] DRY-RUN: would close {} account(s), reclaiming {} lamports", to_close.len(), rent);
+        return Ok(());
     }
+
+    let report = ata::close_accounts(rpcs, kp, &to_close).await;
+    diag!(
+        "{} closed={} burned={} failed={} reclaimed={} fee={}",
+        "[CLEANUP]".green(), report.closed, report.burned, report.failed, report.reclaimed_lamports, report.fee_lamports
+    );
+    rep.stats.lock().await.add_cleanup(&report);
+    Ok(())
 }
 
 // ======================= MAIN =======================
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cfg = Config::load_from_file("config.json")?;
-    let mode = if cfg.dry_run { "DRY-RUN" } else { "LIVE" };
-    println!("SolProRunner — Jupiter v6 (Rust) — {}", mode);
+    let cli = Cli::from_env()?;
+    cli::set_json_stdout(cli.format == OutputFormat::Json);
 
-    println!("[ACCOUNTS] loaded {} tokens:", TOKENS.len());
-    for (sym, mint) in TOKENS.iter() {
-        println!("  {} -> {}", sym, mint);
+    match cli.command.clone() {
+        Command::Help => {
+            print!("{}", cli::USAGE);
+            Ok(())
+        }
+        Command::ValidateConfig => cmd_validate_config(&cli),
+        Command::Run => run_bot(&cli).await,
+        Command::Scan => cmd_scan(&cli).await,
+        Command::Quote { input, output, amount_ui } => cmd_quote(&cli, &input, &output, amount_ui).await,
+        Command::Balances => cmd_balances(&cli).await,
+        Command::Unwind { token } => cmd_unwind(&cli, &token).await,
     }
+}
 
-    // pre-scan informativ
-    {
-        let resolver = Resolver::new().await?;
-        let arbitrage = Arbitrage::new();
-        let tokens = vec!["SOL", "USDT", "BONK", "mSOL"];
+// ======================= setup comun =======================
 
-        match arbitrage.check_all_routes(&resolver, &tokens, 1_000_000, 50).await {
-            Ok(results) => {
-                for (route, profit) in results {
-                    println!("[TRI] Route: {}, Profit est.: {}", route, profit);
-                }
-            }
-            Err(e) => eprintln!("[TRI] scan error: {:#}", e),
+fn load_config(cli: &Cli) -> Result<Config> {
+    let mut cfg = Config::load_from_file(&cli.config_path)?;
+    if cli.dry_run {
+        cfg.dry_run = true;
+    }
+    registry::global().load_config(&cfg);
+    Ok(cfg)
+}
+
+/// Resolver din snapshot-ul local; fără snapshot se încearcă o descărcare acum.
+/// Eșecul nu e fatal: rămân tokenii din config și accounts::TOKENS.
+async fn build_resolver(cfg: &Config) -> Arc<Resolver> {
+    let pinned: HashMap<String, String> = cfg
+        .tokens
+        .iter()
+        .flatten()
+        .filter_map(|(sym, v)| v.as_str().map(|m| (sym.clone(), m.to_string())))
+        .collect();
+    let resolver = Arc::new(Resolver::load(&cfg.resolver, pinned));
+    if resolver.is_empty() {
+        match resolver.refresh().await {
+            Ok(n) => diag!("[RESOLVER] downloaded {} tokens from {}", n, cfg.resolver.url),
+            Err(e) => eprintln!("[RESOLVER] token list unavailable: {:#}", e),
         }
     }
+    resolver
+}
 
-    // wallet
+/// Completează registry-ul cu lista Jupiter și decimals on-chain.
+async fn load_token_registry(resolver: &Resolver, rpcs: &RpcRotator) -> &'static TokenRegistry {
+    let reg = registry::global();
+    let n = reg.merge_jupiter(resolver);
+    diag!("[TOKENS] merged {} tokens from the Jupiter list", n);
+    if let Err(e) = reg.fetch_missing_decimals(rpcs).await {
+        eprintln!("[TOKENS] on-chain decimals failed: {:#}", e);
+    }
+    reg
+}
+
+fn load_wallet(cfg: &Config) -> Result<Arc<Keypair>> {
     let wallet_path = cfg.wallet_keypair_path.as_deref().unwrap_or("wallet.json");
     if !Path::new(wallet_path).exists() {
         return Err(anyhow!("wallet file not found: {}", wallet_path));
     }
-    let kp = Arc::new(load_keypair_from_file(wallet_path)?);
-    println!("Wallet: {}", kp.pubkey());
+    Ok(Arc::new(load_keypair_from_file(wallet_path)?))
+}
 
-    // RPC rotator
-    let rpcs = Arc::new(RpcRotator::new(cfg.rpcs.clone(), cfg.rpc_config.timeout_ms));
+fn build_rpcs(cfg: &Config) -> Result<Arc<RpcRotator>> {
+    let rpcs = Arc::new(RpcRotator::new(cfg.rpcs.clone(), &cfg.rpc_config));
     rpcs.require()?;
-    println!("Using JSON-RPC: {}", rpcs.current_url());
+    Ok(rpcs)
+}
 
-    // Jupiter client
-    let jup = Arc::new(JupiterClient::new(
+fn build_jupiter(cfg: &Config) -> Arc<JupiterClient> {
+    Arc::new(JupiterClient::new(
         cfg.jupiter_base.clone(),
         cfg.prefer_orca,
         cfg.max_slippage_bps,
-    ));
+        cfg.quote_cache.ttl_ms,
+    ))
+}
+
+fn build_jito(cfg: &Config) -> Result<Option<JitoClient>> {
+    if !cfg.jito.use_ {
+        diag!("[JITO] disabled");
+        return Ok(None);
+    }
+    diag!("[JITO] enabled: block_engine={} tip_account={} default_tip={} retries={}",
+        cfg.jito.block_engine, cfg.jito.tip_account, cfg.jito.default_tip_lamports, cfg.jito.max_bundle_retries);
+    Ok(Some(JitoClient::new(
+        cfg.jito.block_engine.clone(),
+        cfg.jito.tip_account.clone(),
+        cfg.jito.default_tip_lamports,
+        cfg.jito.max_bundle_retries,
+    )?))
+}
+
+fn tri_scan_tokens(cfg: &Config) -> Vec<&str> {
+    match cfg.bundles.as_ref().and_then(|b| b.tri_scan.as_ref()) {
+        Some(ts) => ts.tokens.iter().map(|t| t.as_str()).collect(),
+        None => vec!["SOL", "USDT", "BONK", "mSOL"],
+    }
+}
+
+/// simbol sau mint -> mint (config/builtin întâi, apoi lista Jupiter; snapshot, apoi rețea)
+async fn resolve_token_arg(cfg: &Config, arg: &str) -> Result<String> {
+    let reg = registry::global();
+    if let Ok(t) = reg.resolve(arg) {
+        return Ok(t.mint);
+    }
+    let resolver = build_resolver(cfg).await;
+    if let Ok(t) = resolver.resolve(arg) {
+        return Ok(t.address);
+    }
+    resolver.refresh().await?;
+    Ok(resolver.resolve(arg)?.address)
+}
+
+fn print_json(v: &serde_json::Value) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(v)?);
+    Ok(())
+}
+
+// ======================= subcomenzi =======================
+
+fn cmd_validate_config(cli: &Cli) -> Result<()> {
+    let (_, report) = Config::parse_file(&cli.config_path)?;
+    let errors = report.errors();
+
+    match cli.format {
+        OutputFormat::Json => print_json(&serde_json::json!({
+            "config": cli.config_path,
+            "ok": errors.is_empty(),
+            "issues": report.issues,
+        }))?,
+        OutputFormat::Text => {
+            for i in &report.issues {
+                println!("{}", i);
+            }
+            if errors.is_empty() {
+                println!("{} {} is valid", "[OK]".green().bold(), cli.config_path);
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(ConfigError(errors).into());
+    }
+    Ok(())
+}
+
+async fn cmd_scan(cli: &Cli) -> Result<()> {
+    // scan nu trimite nimic, indiferent de config
+    let mut cfg = load_config(cli)?;
+    cfg.dry_run = true;
+
+    let kp = load_wallet(&cfg)?;
+    let rpcs = build_rpcs(&cfg)?;
+    let jup = build_jupiter(&cfg);
+    let limiter = Limiter::new(cfg.limiter.rps, cfg.limiter.burst, cfg.limiter.jitter_ms);
+    let ledger = Arc::new(PnlLedger::new());
+    let jito = build_jito(&cfg)?;
+    let resolver = build_resolver(&cfg).await;
+    let tokens = load_token_registry(&resolver, &rpcs).await;
+
+    let arbitrage = Arbitrage::new(arbs::cycle_costs_lamports(&cfg, jito.as_ref(), 3));
+    let routes = arbitrage
+        .check_all_routes(&jup, &limiter, tokens, &tri_scan_tokens(&cfg), cfg.notional_sol)
+        .await?;
+
+    // bundles configurate: quote + simulare, fără trimitere (dry_run forțat mai sus)
+    run_bundles_once(&cfg, &jup, &rpcs, &kp, jito.as_ref(), &ledger, &limiter, None).await;
+
+    match cli.format {
+        OutputFormat::Json => {
+            let out: Vec<serde_json::Value> = routes
+                .iter()
+                .map(|r| serde_json::json!({
+                    "route": r.label(),
+                    "mints": r.mints,
+                    "amount_in": r.amount_in,
+                    "back_a": r.back_a,
+                    "net_profit_lamports": r.net_profit.to_string(),
+                    "net_profit_bps": r.net_profit_bps(),
+                    "max_impact_bps": r.max_impact_bps(),
+                }))
+                .collect();
+            print_json(&serde_json::json!({ "routes": out }))?;
+        }
+        OutputFormat::Text => {
+            for r in &routes {
+                println!(
+                    "[TRI] Route: {}, back={} pnl={} ({:.2}bp) max_impact={:.2}bp",
+                    r.label(), r.back_a, r.net_profit, r.net_profit_bps(), r.max_impact_bps()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_quote(cli: &Cli, input: &str, output: &str, amount_ui: f64) -> Result<()> {
+    let cfg = load_config(cli)?;
+    let rpcs = build_rpcs(&cfg)?;
+    let jup = build_jupiter(&cfg);
+
+    let in_mint = resolve_token_arg(&cfg, input).await?;
+    let out_mint = resolve_token_arg(&cfg, output).await?;
+    let in_dec = registry::global().decimals(&rpcs, &in_mint).await?;
+    let out_dec = registry::global().decimals(&rpcs, &out_mint).await?;
+    let amount = RawAmount::from_ui(UiAmount(amount_ui), &in_mint, in_dec)?;
+
+    let only_direct = cfg.aggressive.enabled.then_some(cfg.aggressive.only_direct_routes);
+    let q = jup.quote(&in_mint, &out_mint, amount.amount, only_direct).await?;
+    let out_ui = RawAmount::new(q.out_amount, &out_mint, out_dec).ui();
+
+    match cli.format {
+        OutputFormat::Json => print_json(&serde_json::to_value(&q)?)?,
+        OutputFormat::Text => {
+            let route: Vec<&str> = q
+                .route_plan
+                .iter()
+                .map(|s| s.swap_info.label.as_deref().unwrap_or("?"))
+                .collect();
+            println!("{} {} -> {} {}", amount_ui, input, out_ui, output);
+            println!("in_raw={} out_raw={} min_out_raw={}", q.in_amount, q.out_amount, q.other_amount_threshold);
+            println!("impact={:.2}bp slippage={}bp route=[{}]", q.price_impact_bps(), q.slippage_bps, route.join(" > "));
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_balances(cli: &Cli) -> Result<()> {
+    let cfg = load_config(cli)?;
+    let kp = load_wallet(&cfg)?;
+    let rpcs = build_rpcs(&cfg)?;
+    let owner = kp.pubkey();
+
+    let lamports = rpcs.call(|c| async move { c.get_balance(&owner).await }).await?;
+    let balances = rpcs.call(|c| async move { wallet_token_balances(&c, &owner).await }).await?;
+    let mut tokens = Vec::new();
+    for token in registry::global().tracked() {
+        if token.mint == accounts::WSOL_MINT {
+            continue;
+        }
+        let (raw, ui) = balances.get(&token.mint).map(|b| (b.amount, b.ui().0)).unwrap_or((0, 0.0));
+        tokens.push((token.symbol, token.mint, raw, ui));
+    }
+
+    match cli.format {
+        OutputFormat::Json => print_json(&serde_json::json!({
+            "wallet": owner.to_string(),
+            "lamports": lamports,
+            "tokens": tokens
+                .iter()
+                .map(|(sym, mint, raw, ui)| serde_json::json!({
+                    "symbol": sym, "mint": mint, "raw": raw, "ui": ui
+                }))
+                .collect::<Vec<_>>(),
+        }))?,
+        OutputFormat::Text => {
+            println!("Wallet: {}", owner);
+            println!("SOL: {:.9}", Lamports(lamports).as_sol());
+            for (sym, _, _, ui) in &tokens {
+                if *ui > 0.0 {
+                    println!("{}: {}", sym, ui);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_unwind(cli: &Cli, token: &str) -> Result<()> {
+    let cfg = load_config(cli)?;
+    let kp = load_wallet(&cfg)?;
+    let rpcs = build_rpcs(&cfg)?;
+    let jup = build_jupiter(&cfg);
+    let rep = Reporting::new();
+
+    let mint = resolve_token_arg(&cfg, token).await?;
+    let base_mint = cfg
+        .auto_unwind
+        .as_ref()
+        .map(|au| au.base_mint.clone())
+        .unwrap_or_else(|| accounts::WSOL_MINT.to_string());
+    if mint == base_mint {
+        return Err(anyhow!("{} is the unwind base mint", token));
+    }
+
+    let owner = kp.pubkey();
+    let mint_ref = mint.as_str();
+    let balance = rpcs
+        .call(|c| async move { spl_balance(&c, &owner, mint_ref).await })
+        .await?;
+    // rezerva și sell_fraction din auto_unwind se respectă și la comanda manuală
+    let (reserve_ui, fraction) = match cfg.auto_unwind.as_ref() {
+        Some(au) => {
+            let rule = unwind_rule(au, &symbol_for_mint(&mint).unwrap_or_default(), &mint);
+            (rule.reserve_ui, rule.sell_fraction)
+        }
+        None => (0.0, 0.995),
+    };
+    let reserve = UiAmount(reserve_ui).to_raw(balance.decimals).unwrap_or(u64::MAX);
+    let sell_raw = RawAmount::new(balance.amount.saturating_sub(reserve), &mint, balance.decimals)
+        .fraction(fraction)
+        .amount;
+    diag!("[UNWIND] {} ({}) balance_ui={:.9} -> selling_raw={}", token, mint, balance.ui().0, sell_raw);
+    if sell_raw == 0 {
+        return Ok(());
+    }
+
+    // comanda explicită vinde oricum (ca "always"); procesul nu are cost basis
+    let sig = swap_single_leg(&cfg, &jup, &rpcs, &kp, &mint, &base_mint, sell_raw, SellGuard::FORCED, &rep).await?;
+
+    // așteptăm rezultatul aici: procesul se termină după comandă
+    let outcome = match &sig {
+        Some(s) => Some(confirm::wait_for_outcome(&rpcs, &Signature::from_str(s)?, None).await?),
+        None => None,
+    };
+
+    match cli.format {
+        OutputFormat::Json => print_json(&serde_json::json!({
+            "mint": mint,
+            "sell_raw": sell_raw,
+            "signature": sig,
+            "outcome": outcome.as_ref().map(|o| o.tag()),
+        }))?,
+        OutputFormat::Text => {
+            if let (Some(s), Some(o)) = (&sig, &outcome) {
+                diag!("[UNWIND] sig={} {}", s, o.tag());
+            }
+        }
+    }
+    Ok(())
+}
+
+// ======================= RUN (bot complet) =======================
+
+async fn run_bot(cli: &Cli) -> Result<()> {
+    let cfg = load_config(cli)?;
+    let mode = if cfg.dry_run { "DRY-RUN" } else { "LIVE" };
+    diag!("SolProRunner — Jupiter v6 (Rust) — {}", mode);
+
+    // jurnal JSON-lines cu evenimentele de trading (logs.json_events)
+    events::init(cfg.logs.as_ref())?;
+
+    // wallet
+    let kp = load_wallet(&cfg)?;
+    diag!("Wallet: {}", kp.pubkey());
+
+    // RPC rotator
+    let rpcs = build_rpcs(&cfg)?;
+    rpcs.spawn_health_probe(cfg.rpc_config.probe_every_ms);
+    diag!("Using JSON-RPC: {}", rpcs.current_url());
+
+    // Jupiter client
+    let jup = build_jupiter(&cfg);
+    spawn_quote_cache_gc(jup.clone(), cfg.quote_cache.gc_every_ms);
+
+    // tokeni: config + accounts::TOKENS + lista Jupiter + decimals on-chain
+    let resolver = build_resolver(&cfg).await;
+    resolver.spawn_refresh(cfg.resolver.refresh_every_ms);
+    let tokens = load_token_registry(&resolver, &rpcs).await;
+    let tracked = tokens.tracked();
+    diag!("[TOKENS] {} known, {} tracked:", tokens.count(), tracked.len());
+    for t in &tracked {
+        diag!("  {} -> {} (decimals {:?})", t.symbol, t.mint, t.decimals);
+    }
+
+    // ATA-uri pentru mint-urile configurate, create înainte de primul swap (în dry-run doar raport)
+    if let Some(setup) = cfg.account_setup.as_ref().filter(|s| s.ensure_atas && s.on_startup) {
+        let mints = ata::setup_mints(&cfg);
+        let create = setup.create_if_missing && !cfg.dry_run;
+        match ata::ensure_atas(&rpcs, &kp, &mints, create).await {
+            Ok(r) => {
+                diag!("[ATA] {}", r.render());
+                if !create && !r.missing.is_empty() {
+                    diag!("{} missing: {:?}", "[ATA]".yellow(), r.missing);
+                }
+            }
+            Err(e) => eprintln!("[ATA] setup failed: {:#}", e),
+        }
+    }
+
+    // config partajat (hot-reload)
+    let shared = Arc::new(SharedConfig::new(&cli.config_path, cfg.clone()).force_dry_run(cli.dry_run));
+    if cfg.reload.enabled {
+        shared.spawn_watcher(cfg.reload.check_every_ms);
+        diag!("[CONFIG] watching {} every {}ms", cli.config_path, cfg.reload.check_every_ms);
+    }
 
     // Limiter
-    let limiter = Arc::new(Limiter::new(
+    let mut limiter = Arc::new(Limiter::new(
         cfg.limiter.rps,
         cfg.limiter.burst,
         cfg.limiter.jitter_ms,
     ));
+    // buclele din fundal care fac quote-uri văd limiter-ul curent și după reload
+    let (limiter_tx, limiter_rx) = watch::channel(limiter.clone());
 
     // NEW: stats shared + reporter
-    let stats = Arc::new(Mutex::new(Stats::new()));
-    spawn_hourly_reporter(stats.clone()).await;
-
-    // după ce ai cfg, jup, limiter, stats etc.
-    let jito_cfg_opt: Option<&config::JitoConfig> =
-    if cfg.jito.use_ { Some(&cfg.jito) } else { None };
+    let rep = Reporting::new();
+    spawn_hourly_reporter(rep.stats.clone(), jup.clone(), rpcs.clone(), rep.ledger.clone()).await;
+
+    // Prometheus: GET /metrics (latențe, erori, decizii, PnL, bundle-uri, balanțe)
+    if let Some(m) = cfg.metrics.as_ref().filter(|m| m.enabled) {
+        metrics::spawn_server(&m.listen, rep.ledger.clone()).await?;
+        if m.balances_every_ms > 0 {
+            spawn_balance_metrics(rpcs.clone(), kp.pubkey(), m.balances_every_ms);
+        }
+    }
 
     // +++ JITO INIT (opțional din config)
-    let jito = if cfg.jito.use_ {
-     println!("[JITO] enabled: block_engine={} tip_account={} default_tip={} retries={}",
-        cfg.jito.block_engine, cfg.jito.tip_account, cfg.jito.default_tip_lamports, cfg.jito.max_bundle_retries);
-    Some(JitoClient::new(
-        cfg.jito.block_engine.clone(),
-        cfg.jito.tip_account.clone(),
-        cfg.jito.default_tip_lamports,
-        cfg.jito.max_bundle_retries,
-    )?)
-} else {
-    println!("[JITO] disabled");
-    None
-};
+    let jito = build_jito(&cfg)?;
+
+    // pre-scan informativ (rutele triunghiulare, clasate după profit net)
+    {
+        let tri_tokens = tri_scan_tokens(&cfg);
+        let arbitrage = Arbitrage::new(arbs::cycle_costs_lamports(&cfg, jito.as_ref(), 3));
+        match arbitrage.check_all_routes(&jup, &limiter, tokens, &tri_tokens, cfg.notional_sol).await {
+            Ok(results) => {
+                for r in results {
+                    diag!(
+                        "[TRI] Route: {}, back={} pnl={} ({:.2}bp) max_impact={:.2}bp",
+                        r.label(), r.back_a, r.net_profit, r.net_profit_bps(), r.max_impact_bps()
+                    );
+                }
+            }
+            Err(e) => eprintln!("[TRI] scan error: {:#}", e),
+        }
+    }
 
     // AUTO-UNWIND: pornește în paralel, dacă e activat
     {
-        let cfg_arc = Arc::new(cfg.clone());
+        let cfg_arc = shared.clone();
         let jup_arc = jup.clone();
         let rpcs_arc = rpcs.clone();
         let kp_arc = kp.clone();
-        let stats_arc = stats.clone();
+        let rep_arc = rep.clone();
         tokio::spawn(async move {
-            auto_unwind_loop(cfg_arc, jup_arc, rpcs_arc, kp_arc, stats_arc).await; 
+            auto_unwind_loop(cfg_arc, jup_arc, rpcs_arc, kp_arc, rep_arc).await;
         });
     }
 
+    // CLEANUP: conturi de token goale / dust, periodic (dacă e activat)
+    tokio::spawn(account_cleanup_loop(
+        shared.clone(),
+        jup.clone(),
+        rpcs.clone(),
+        kp.clone(),
+        limiter_rx,
+        rep.clone(),
+    ));
+
     // loop principal (rutele clasice)
+    let mut limiter_cfg = cfg.limiter.clone();
     loop {
-        let jito_cfg_opt = if cfg.jito.use_ { Some(&cfg.jito) } else { None };
-        run_bundles_once(&cfg, &jup, &rpcs, &kp, jito_cfg_opt).await;
+        // fiecare trecere vede ultimul config valid
+        let cfg = shared.get();
+        if cfg.limiter != limiter_cfg {
+            limiter = Arc::new(Limiter::new(
+                cfg.limiter.rps,
+                cfg.limiter.burst,
+                cfg.limiter.jitter_ms,
+            ));
+            limiter_cfg = cfg.limiter.clone();
+            limiter_tx.send_replace(limiter.clone());
+            diag!("[LIMITER] rebuilt: rps={} burst={} jitter={}ms",
+                limiter_cfg.rps, limiter_cfg.burst, limiter_cfg.jitter_ms);
+        }
 
-        for pair in &cfg.pairs {
-            limiter.wait().await;
-            if let Err(e) = handle_pair(&cfg, &jup, &rpcs, &kp, pair, &stats).await {
-                eprintln!("[PAIR {}→{}] ERR {}", pair.input_mint, pair.output_mint, e);
+        // bundle pass și perechile rulează în paralel (RPC-ul e async, limiter-ul e comun)
+        let bundles_pass = run_bundles_once(&cfg, &jup, &rpcs, &kp, jito.as_ref(), &rep.ledger, &limiter, Some(tokens));
+        let pairs_pass = async {
+            for pair in &cfg.pairs {
+                limiter.wait().await;
+                if let Err(e) = handle_pair(&cfg, &jup, &rpcs, &kp, &limiter, pair, &rep).await {
+                    eprintln!("[PAIR {}→{}] ERR {}", pair.input_mint, pair.output_mint, e);
+                }
+                tokio::time::sleep(Duration::from_millis(200)).await;
             }
-            tokio::time::sleep(Duration::from_millis(200)).await;
-        }
+        };
+        tokio::join!(bundles_pass, pairs_pass);
 
-        println!("Iteration complete, sleeping for {} ms...", cfg.poll_ms);
+        diag!("Iteration complete, sleeping for {} ms...", cfg.poll_ms);
         tokio::time::sleep(Duration::from_millis(cfg.poll_ms)).await;
     }
 }
@@ -470,22 +1484,47 @@ async fn handle_pair(
     jup: &JupiterClient,
     rpcs: &Arc<RpcRotator>,
     kp: &Arc<Keypair>,
+    limiter: &Limiter,
     pair: &Pair,
-    stats: &Arc<Mutex<Stats>>, // NEW
+    rep: &Reporting,
 ) -> anyhow::Result<()> {
-    let client = rpcs.client();
-    let balance = client.get_balance(&kp.pubkey()).unwrap_or(0);
-    let in_u: u64 = sol_to_lamports(cfg.notional_sol);
-    let need = in_u + cfg.fees.lamports_per_signature;
-    if !cfg.dry_run && balance < need {
-        println!(
+    let Reporting { stats, ledger } = rep;
+    let owner = kp.pubkey();
+    let balance = Lamports(rpcs.call(|c| async move { c.get_balance(&owner).await }).await.unwrap_or(0));
+    let tx_fee = Lamports(cfg.fees.lamports_per_signature);
+
+    // pair.notional (UI, în inputMint) sau notional_sol convertit la valoare egală
+    let (notional, rate) = arbs::notional_in(cfg, jup, rpcs, limiter, &pair.input_mint, pair.notional).await?;
+    let in_u: u64 = notional.amount;
+    let key = Stats::pair_key(&pair.input_mint, &pair.output_mint);
+
+    let (held, need) = if notional.is_sol() {
+        (balance.0, Lamports(in_u).saturating_add(tx_fee).0)
+    } else {
+        let mint = pair.input_mint.as_str();
+        let held = rpcs
+            .call(|c| async move { spl_balance(&c, &owner, mint).await })
+            .await
+            .map(|b| b.amount)
+            .unwrap_or(0);
+        // fee-ul se plătește oricum în SOL
+        (if balance < tx_fee { 0 } else { held }, in_u)
+    };
+    if !cfg.dry_run && held < need {
+        diag!(
             "[PAIR {}→{}] Skipping, balance {} too low vs required {}",
-            pair.input_mint, pair.output_mint, balance, need
+            pair.input_mint, pair.output_mint, held, need
         );
+        events::emit(Event::DecisionSkipped {
+            path: TradePath::Pair.as_str(),
+            key,
+            reason: "balance",
+            detail: format!("held {} < need {}", held, need),
+        });
         // skip count
         {
             let mut st = stats.lock().await;
-            st.inc_skip();
+            st.inc_skip("balance");
         }
         return Ok(());
     }
@@ -501,99 +1540,115 @@ async fn handle_pair(
     } else {
         None
     };
-    println!(
+    diag!(
         "[PAIR {}→{}] Running (balance {}, dry_run {}, direct {:?})",
         pair.input_mint, pair.output_mint, balance, cfg.dry_run, only_direct
     );
 
-    let quote_fwd = jup
-        .quote(&pair.input_mint, &pair.output_mint, in_u, only_direct)
-        .await?;
+    let quote_fwd = match jup
+        .quote_with_slippage(&pair.input_mint, &pair.output_mint, in_u, only_direct, pair.slippage_bps)
+        .await
+    {
+        Ok(q) => q,
+        Err(e) => {
+            stats.lock().await.inc_skip("quote_error");
+            return Err(e);
+        }
+    };
 
-    let out_u_est_b: u64 = quote_fwd
-        .get("outAmount").and_then(|x| x.as_str())
-        .and_then(|s| s.parse::<u64>().ok())
-        .unwrap_or(0);
-
-    let impact: f64 = quote_fwd
-        .get("priceImpactPct").and_then(|x| x.as_str())
-        .and_then(|s| s.parse::<f64>().ok())
-        .unwrap_or(0.0);
-
-    let amm_label = quote_fwd
-        .get("routePlan").and_then(|rp| rp.get(0))
-        .and_then(|r0| r0.get("swapInfo"))
-        .and_then(|si| si.get("label"))
-        .and_then(|l| l.as_str())
-        .unwrap_or("?");
-
-    println!("[QUOTE FWD] outAmount(B)={}, amm={}, priceImpact={}", out_u_est_b, amm_label, impact);
-
-    let max_impact = 0.001_f64;
-    if impact > max_impact {
-        println!(
-            "{} {}",
-            "[DECISION] NO-EXEC".yellow(),
-            format!("impact too high ({})", impact)
-        );
+    let out_u_est_b: u64 = quote_fwd.out_amount;
+    let impact: f64 = quote_fwd.price_impact_pct;
+    let amm_label = quote_fwd.amm_label();
+
+    diag!("[QUOTE FWD] outAmount(B)={}, amm={}, priceImpact={}", out_u_est_b, amm_label, impact);
+
+    // override per pereche, altfel risk.pair_max_impact_bps
+    let max_impact_bps = risk::max_impact_bps(cfg, TradePath::Pair, pair.max_price_impact_bps);
+    if let Err(r) = risk::check_leg(&quote_fwd, 1, max_impact_bps) {
+        diag!("{} {}", "[DECISION] NO-EXEC".yellow(), r);
+        risk::record(TradePath::Pair, &key, &r);
         let mut st = stats.lock().await;
-        st.inc_skip();
+        st.inc_skip(r.kind());
         return Ok(());
     }
 
-    let quote_rev = jup
-        .quote(&pair.output_mint, &pair.input_mint, out_u_est_b, only_direct)
-        .await?;
+    let quote_rev = match jup
+        .quote_with_slippage(&pair.output_mint, &pair.input_mint, out_u_est_b, only_direct, pair.slippage_bps)
+        .await
+    {
+        Ok(q) => q,
+        Err(e) => {
+            stats.lock().await.inc_skip("quote_error");
+            return Err(e);
+        }
+    };
+    if let Err(r) = risk::check_leg(&quote_rev, 2, max_impact_bps) {
+        diag!("{} {}", "[DECISION] NO-EXEC".yellow(), r);
+        risk::record(TradePath::Pair, &key, &r);
+        let mut st = stats.lock().await;
+        st.inc_skip(r.kind());
+        return Ok(());
+    }
 
-    let back_to_a_est: u64 = quote_rev
-        .get("outAmount").and_then(|x| x.as_str())
-        .and_then(|s| s.parse::<u64>().ok())
-        .unwrap_or(0);
+    let back_to_a_est: u64 = quote_rev.out_amount;
 
-    let fee_buffer: u64 =
-        2 * cfg.fees.lamports_per_signature + cfg.fees.priority_fee_lamports;
-    let pnl_lamports: i128 = back_to_a_est as i128 - in_u as i128 - fee_buffer as i128;
+    // tot PnL-ul e în unitățile raw ale lui A; fee-urile (lamports) se convertesc la curs
+    let fee_buffer: u64 = rate
+        .to_raw(Lamports(2 * cfg.fees.lamports_per_signature + cfg.fees.priority_fee_lamports))
+        .amount;
+    let pnl_raw: i128 = back_to_a_est as i128 - in_u as i128 - fee_buffer as i128;
 
-    let mut thresh_lamports: i128 = 0;
-    if let Some(mp) = &cfg.min_profit {
+    let mut thresh_raw: i128 = 0;
+    if let Some(bps) = pair.min_profit_bps {
+        // override per pereche: relativ la notional, înaintea pragurilor globale
+        thresh_raw = ((in_u as u128) * (bps as u128) / 10_000u128) as i128;
+    } else if let Some(mp) = &cfg.min_profit {
         match mp.mode.as_str() {
-            "abs" => {
-                if mp.denom.as_deref() == Some("SOL") {
-                    let v = (mp.value * 1_000_000_000.0).round() as i128;
-                    thresh_lamports = v.max(0);
-                }
+            "abs" if mp.denom.as_deref() == Some("SOL") => {
+                thresh_raw = rate.to_raw(Lamports::from_sol(mp.value)?).amount as i128;
             }
             "pct" => {
                 let v = ((in_u as f64) * mp.value).round() as i128;
-                thresh_lamports = v.max(0);
+                thresh_raw = v.max(0);
             }
             _ => {}
         }
     }
-    if thresh_lamports == 0 {
+    if thresh_raw == 0 && pair.min_profit_bps.is_none() {
         let v = ((in_u as u128) * (cfg.min_profit_bps as u128) / 10_000u128) as i128;
-        thresh_lamports = v.max(0);
+        thresh_raw = v.max(0);
     }
 
-    println!(
+    diag!(
         "[CYCLE] in(A)={}, back(A)_est={}, fee_buf={}, pnl={}, thresh={}",
-        in_u, back_to_a_est, fee_buffer, pnl_lamports, thresh_lamports
+        notional, back_to_a_est, fee_buffer, pnl_raw, thresh_raw
     );
-
-    if pnl_lamports < thresh_lamports {
-        println!(
-     "{}: cycle pnl {} < threshold {} (skip)",
-     "[DECISION] NO-EXEC".red().bold(),
-     pnl_lamports,
-     thresh_lamports
-    );
-    let mut st = stats.lock().await;
-        st.inc_skip();
+    events::emit(Event::CycleEvaluated {
+        path: TradePath::Pair.as_str(),
+        key: key.clone(),
+        amount_in: in_u,
+        back: back_to_a_est,
+        costs: fee_buffer,
+        pnl: pnl_raw,
+        thresh: thresh_raw,
+    });
+    stats.lock().await.record_cycle(&key, in_u, pnl_raw);
+
+    if pnl_raw < thresh_raw {
+        diag!(
+            "{}: cycle pnl {} < threshold {} (skip)",
+            "[DECISION] NO-EXEC".red().bold(),
+            pnl_raw,
+            thresh_raw
+        );
+        risk::record(TradePath::Pair, &key, &Reject::Profit { pnl: pnl_raw, thresh: thresh_raw });
+        let mut st = stats.lock().await;
+        st.inc_skip("profit");
         return Ok(());
     }
 
     if cfg.dry_run {
-        println!("[DRY-RUN] Guard passed, would EXEC now.");
+        diag!("[DRY-RUN] Guard passed, would EXEC now.");
         let mut st = stats.lock().await;
         st.inc_exec(&pair.input_mint, &pair.output_mint);
         return Ok(());
@@ -603,8 +1658,8 @@ async fn handle_pair(
     let user_pubkey = kp.pubkey().to_string();
     let tip: u64 = cfg.fees.priority_fee_lamports;
 
-    let swap_b64: String = jup.swap_tx(&quote_fwd, &user_pubkey, tip).await?;
-    let tx_bytes = BASE64_STANDARD.decode(&swap_b64)?;
+    let swap = jup.swap(&quote_fwd, &user_pubkey, tip).await?;
+    let tx_bytes = BASE64_STANDARD.decode(&swap.swap_transaction)?;
     let mut vtx: VersionedTransaction = bincode::deserialize(&tx_bytes)?;
 
     let msg: &VersionedMessage = &vtx.message;
@@ -615,26 +1670,148 @@ async fn handle_pair(
         vtx.signatures[0] = sig;
     }
 
-    let sig_str = client.send_transaction_with_config(
-        &vtx,
-        RpcSendTransactionConfig {
-            skip_preflight: true,
-            max_retries: Some(1),
-            preflight_commitment: Some(CommitmentConfig::confirmed().commitment),
-            ..Default::default()
-        },
-    )?;
-    println!(
+    let vtx = &vtx;
+    let sig_str = rpcs.call_send(|c| async move {
+        c.send_transaction_with_config(
+            vtx,
+            RpcSendTransactionConfig {
+                skip_preflight: true,
+                max_retries: Some(1),
+                preflight_commitment: Some(CommitmentConfig::confirmed().commitment),
+                ..Default::default()
+            },
+        ).await
+    }).await?;
+    diag!(
     "{} sig={}",
-    "[EXECUTED]".green().bold(),
+    "[SENT]".green(),
     sig_str
     );
+    events::emit(Event::TxSent { path: TradePath::Pair.as_str(), key: key.clone(), sig: sig_str.to_string() });
+
+    // PnL realizat: B primit se evaluează în A la cursul din quote-ul invers
+    let pnl_ctx = TradeCtx {
+        key,
+        base_mint: pair.input_mint.clone(),
+        estimated: pnl_raw,
+        tip_lamports: 0,
+        sol_rate: Some(rate.clone()),
+        mark_to: (out_u_est_b > 0).then(|| MarkPrice {
+            mint: pair.output_mint.clone(),
+            base_per_unit: back_to_a_est as f64 / out_u_est_b as f64,
+        }),
+    };
 
-    // contor exec
-    {
-        let mut st = stats.lock().await;
-        st.inc_exec(&pair.input_mint, &pair.output_mint);
-    }
+    // contor exec: doar după ce tx-ul aterizează
+    stats.lock().await.inc_sent();
+    spawn_confirm_and_record(
+        rpcs.clone(),
+        stats.clone(),
+        sig_str,
+        swap.last_valid_block_height,
+        ExecKind::Pair {
+            input_mint: pair.input_mint.clone(),
+            output_mint: pair.output_mint.clone(),
+        },
+        Some((ledger.clone(), kp.pubkey(), pnl_ctx)),
+    );
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+    const FEES: Fees = Fees { lamports_per_signature: 5_000, priority_fee_lamports: 10_000 };
+
+    fn au(mode: &str) -> AutoUnwindCfg {
+        AutoUnwindCfg {
+            enabled: true,
+            base_mint: accounts::WSOL_MINT.to_string(),
+            min_token_ui: 0.0,
+            mode: mode.to_string(),
+            min_profit_bps: 0,
+            check_every_ms: 1_000,
+            max_hold_ms: None,
+            tokens: Vec::new(),
+            deny: Vec::new(),
+            sell_fraction: 1.0,
+            per_token: HashMap::new(),
+        }
+    }
+
+    /// 1000 raw cumpărate cu 2000 lamports -> 2 per unitate
+    fn position(base_mint: &str) -> Position {
+        Position { base_mint: base_mint.to_string(), qty: 1_000, cost: 2_000, opened: Instant::now() }
+    }
+
+    fn sell(plan: UnwindPlan) -> SellGuard {
+        match plan {
+            UnwindPlan::Sell(g) => g,
+            UnwindPlan::Hold(why) => panic!("expected Sell, got Hold({})", why),
+        }
+    }
+
+    #[test]
+    fn always_sells_without_a_floor() {
+        let p = position(accounts::WSOL_MINT);
+        let g = sell(unwind_plan(&au("always"), &FEES, Some(&p), Duration::ZERO, 500));
+        assert_eq!(g, SellGuard { min_out: 0, basis_per_unit: Some(2.0) });
+    }
+
+    #[test]
+    fn pnl_gt_0_needs_cost_plus_fee_plus_one() {
+        let p = position(accounts::WSOL_MINT);
+        let g = sell(unwind_plan(&au("pnl_gt_0"), &FEES, Some(&p), Duration::ZERO, 500));
+        assert_eq!(g, SellGuard { min_out: 1_000 + 15_000 + 1, basis_per_unit: Some(2.0) });
+    }
+
+    #[test]
+    fn bps_adds_the_margin_on_cost() {
+        let mut cfg = au("bps");
+        cfg.min_profit_bps = 100;
+        let p = position(accounts::WSOL_MINT);
+        let g = sell(unwind_plan(&cfg, &FEES, Some(&p), Duration::ZERO, 500));
+        assert_eq!(g.min_out, 1_000 + 15_000 + 10);
+    }
+
+    #[test]
+    fn fee_is_not_added_for_a_non_sol_base() {
+        let mut cfg = au("pnl_gt_0");
+        cfg.base_mint = TOKEN.to_string();
+        let p = position(TOKEN);
+        let g = sell(unwind_plan(&cfg, &FEES, Some(&p), Duration::ZERO, 500));
+        assert_eq!(g.min_out, 1_001);
+    }
+
+    #[test]
+    fn profit_modes_hold_without_basis() {
+        for mode in ["pnl_gt_0", "bps"] {
+            assert!(matches!(
+                unwind_plan(&au(mode), &FEES, None, Duration::ZERO, 500),
+                UnwindPlan::Hold("no cost basis")
+            ));
+        }
+        // costul într-o altă bază nu contează ca basis
+        let p = position(TOKEN);
+        assert!(matches!(
+            unwind_plan(&au("pnl_gt_0"), &FEES, Some(&p), Duration::ZERO, 500),
+            UnwindPlan::Hold("no cost basis")
+        ));
+    }
+
+    #[test]
+    fn expired_hold_sells_anyway() {
+        let mut cfg = au("pnl_gt_0");
+        cfg.max_hold_ms = Some(60_000);
+        let g = sell(unwind_plan(&cfg, &FEES, None, Duration::from_secs(61), 500));
+        assert_eq!(g, SellGuard::FORCED);
+
+        assert!(matches!(
+            unwind_plan(&cfg, &FEES, None, Duration::from_secs(59), 500),
+            UnwindPlan::Hold(_)
+        ));
+    }
+}
@@ -0,0 +1,266 @@
+// src/metrics.rs
+use anyhow::{Context as _, Result};
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::events::Event;
+use crate::pnl::PnlLedger;
+
+/// Limitele (le) histogramelor, în secunde.
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Tot ce exportăm: (nume, tip, help).
+const METRICS: &[(&str, &str, &str)] = &[
+    ("solpro_http_request_seconds", "histogram", "Latency of Jupiter and Jito HTTP requests per endpoint"),
+    ("solpro_http_errors_total", "counter", "Failed Jupiter and Jito HTTP requests by status"),
+    ("solpro_rpc_request_seconds", "histogram", "Latency of successful Solana RPC calls per endpoint"),
+    ("solpro_rpc_errors_total", "counter", "Failed Solana RPC calls per endpoint and status"),
+    ("solpro_limiter_wait_seconds", "histogram", "Time spent waiting on the quote rate limiter"),
+    ("solpro_cycles_evaluated_total", "counter", "Fully quoted cycles per path"),
+    ("solpro_decisions_total", "counter", "Decisions per path: sent, or the skip reason"),
+    ("solpro_tx_outcomes_total", "counter", "Final outcome of sent transactions (pair, unwind) and Jito bundles (two_leg, three_leg)"),
+    ("solpro_unwinds_total", "counter", "Auto-unwind sells that landed"),
+    ("solpro_pnl_estimated_raw", "gauge", "Estimated PnL per key, raw units of base_mint"),
+    ("solpro_pnl_realized_raw", "gauge", "Realized PnL per key from on-chain balances, raw units of base_mint"),
+    ("solpro_wallet_balance", "gauge", "Wallet balance per token, UI units"),
+];
+
+struct Histogram {
+    /// necumulat, câte unul per limită din LATENCY_BUCKETS
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+enum Value {
+    Scalar(f64),
+    Histogram(Histogram),
+}
+
+/// (nume, etichete deja formatate) -> valoare; BTreeMap ca seriile aceluiași nume să fie adiacente.
+static REGISTRY: Lazy<Mutex<BTreeMap<(&'static str, String), Value>>> = Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+pub fn inc(name: &'static str, labels: &[(&str, &str)]) {
+    add(name, labels, 1.0);
+}
+
+pub fn add(name: &'static str, labels: &[(&str, &str)], v: f64) {
+    let mut reg = REGISTRY.lock().unwrap();
+    if let Value::Scalar(x) = reg.entry((name, label_set(labels))).or_insert(Value::Scalar(0.0)) {
+        *x += v;
+    }
+}
+
+pub fn set(name: &'static str, labels: &[(&str, &str)], v: f64) {
+    REGISTRY.lock().unwrap().insert((name, label_set(labels)), Value::Scalar(v));
+}
+
+pub fn observe(name: &'static str, labels: &[(&str, &str)], secs: f64) {
+    let mut reg = REGISTRY.lock().unwrap();
+    let entry = reg.entry((name, label_set(labels))).or_insert_with(|| {
+        Value::Histogram(Histogram { counts: vec![0; LATENCY_BUCKETS.len()], sum: 0.0, count: 0 })
+    });
+    if let Value::Histogram(h) = entry {
+        if let Some(i) = LATENCY_BUCKETS.iter().position(|b| secs <= *b) {
+            h.counts[i] += 1;
+        }
+        h.sum += secs;
+        h.count += 1;
+    }
+}
+
+/// Latența și, la eșec, statusul unui request HTTP (Jupiter, Jito). Răspunsul trece neschimbat;
+/// `error_for_status` rămâne la apelant.
+pub fn track_http(
+    service: &str,
+    endpoint: &str,
+    t0: Instant,
+    res: reqwest::Result<reqwest::Response>,
+) -> reqwest::Result<reqwest::Response> {
+    observe("solpro_http_request_seconds", &[("service", service), ("endpoint", endpoint)], t0.elapsed().as_secs_f64());
+    let status = match &res {
+        Ok(r) if r.status().is_success() => None,
+        Ok(r) => Some(r.status().as_u16().to_string()),
+        Err(e) => Some(http_error_status(e)),
+    };
+    if let Some(s) = status {
+        inc("solpro_http_errors_total", &[("service", service), ("endpoint", endpoint), ("status", s.as_str())]);
+    }
+    res
+}
+
+/// Codul HTTP dacă există, altfel felul erorii de transport.
+pub fn http_error_status(e: &reqwest::Error) -> String {
+    match e.status() {
+        Some(s) => s.as_u16().to_string(),
+        None if e.is_timeout() => "timeout".into(),
+        None if e.is_connect() => "connect".into(),
+        None => "transport".into(),
+    }
+}
+
+/// Eticheta unui endpoint RPC: doar host-ul (URL-urile pot conține api-key în path/query).
+pub fn endpoint_label(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| "?".into())
+}
+
+/// Contoarele derivate din jurnalul de evenimente (`events::emit`).
+pub fn on_event(ev: &Event) {
+    match ev {
+        Event::QuoteFetched { .. } => {}
+        Event::CycleEvaluated { path, .. } => inc("solpro_cycles_evaluated_total", &[("path", *path)]),
+        Event::DecisionSkipped { path, reason, .. } => {
+            inc("solpro_decisions_total", &[("path", *path), ("decision", *reason)])
+        }
+        Event::TxSent { path, .. } => inc("solpro_decisions_total", &[("path", *path), ("decision", "sent")]),
+        Event::TxConfirmed { path, outcome, .. } => {
+            inc("solpro_tx_outcomes_total", &[("path", *path), ("outcome", *outcome)])
+        }
+        Event::UnwindExecuted { mint, symbol, .. } => {
+            let token = if symbol.is_empty() { mint.as_str() } else { symbol.as_str() };
+            inc("solpro_unwinds_total", &[("token", token)])
+        }
+    }
+}
+
+/// Textul pentru `/metrics` (format Prometheus 0.0.4); PnL-ul se citește din ledger la fiecare scrape.
+pub fn render(ledger: &PnlLedger) -> String {
+    for (key, e) in ledger.snapshot() {
+        let l = [("key", key.as_str()), ("base_mint", e.base_mint.as_str())];
+        set("solpro_pnl_estimated_raw", &l, e.estimated as f64);
+        set("solpro_pnl_realized_raw", &l, e.realized as f64);
+    }
+
+    let reg = REGISTRY.lock().unwrap();
+    let mut out = String::new();
+    let mut last = "";
+    for ((name, labels), v) in reg.iter() {
+        if *name != last {
+            let (kind, help) = describe(name);
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            last = *name;
+        }
+        match v {
+            Value::Scalar(x) => {
+                let _ = writeln!(out, "{}{} {}", name, braces(labels, None), x);
+            }
+            Value::Histogram(h) => {
+                let mut cum = 0;
+                for (b, c) in LATENCY_BUCKETS.iter().zip(&h.counts) {
+                    cum += c;
+                    let _ = writeln!(out, "{}_bucket{} {}", name, braces(labels, Some(&b.to_string())), cum);
+                }
+                let _ = writeln!(out, "{}_bucket{} {}", name, braces(labels, Some("+Inf")), h.count);
+                let _ = writeln!(out, "{}_sum{} {}", name, braces(labels, None), h.sum);
+                let _ = writeln!(out, "{}_count{} {}", name, braces(labels, None), h.count);
+            }
+        }
+    }
+    out
+}
+
+fn describe(name: &str) -> (&'static str, &'static str) {
+    METRICS
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, kind, help)| (*kind, *help))
+        .unwrap_or(("untyped", ""))
+}
+
+/// `k1="v1",k2="v2"` cu escape pentru `\`, `"` și newline.
+fn label_set(labels: &[(&str, &str)]) -> String {
+    labels
+        .iter()
+        .map(|(k, v)| {
+            let v = v.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+            format!("{}=\"{}\"", k, v)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn braces(labels: &str, le: Option<&str>) -> String {
+    match (labels.is_empty(), le) {
+        (true, None) => String::new(),
+        (false, None) => format!("{{{}}}", labels),
+        (true, Some(le)) => format!("{{le=\"{}\"}}", le),
+        (false, Some(le)) => format!("{{{},le=\"{}\"}}", labels, le),
+    }
+}
+
+/* ===================== HTTP ===================== */
+
+/// Pornește serverul (GET /metrics, GET /health); bind-ul eșuat se raportează la start.
+pub async fn spawn_server(listen: &str, ledger: Arc<PnlLedger>) -> Result<()> {
+    let listener = TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("metrics.listen {}", listen))?;
+    diag!("[METRICS] serving http://{}/metrics", listener.local_addr()?);
+
+    tokio::spawn(async move {
+        loop {
+            let sock = match listener.accept().await {
+                Ok((sock, _)) => sock,
+                Err(e) => {
+                    eprintln!("[METRICS] accept: {}", e);
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    continue;
+                }
+            };
+            let ledger = ledger.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle(sock, &ledger).await {
+                    eprintln!("[METRICS] request: {}", e);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+/// Un request pe conexiune (Connection: close); contează doar linia de request.
+async fn handle(mut sock: TcpStream, ledger: &PnlLedger) -> io::Result<()> {
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") && buf.len() < 8192 {
+        let n = tokio::time::timeout(Duration::from_secs(5), sock.read(&mut chunk))
+            .await
+            .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))??;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let req = String::from_utf8_lossy(&buf);
+    let mut parts = req.lines().next().unwrap_or("").split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("").split('?').next().unwrap_or("");
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/metrics") => ("200 OK", "text/plain; version=0.0.4; charset=utf-8", render(ledger)),
+        ("GET", "/health") => ("200 OK", "text/plain; charset=utf-8", "ok\n".to_string()),
+        ("GET", _) => ("404 Not Found", "text/plain; charset=utf-8", "not found\n".to_string()),
+        _ => ("405 Method Not Allowed", "text/plain; charset=utf-8", "method not allowed\n".to_string()),
+    };
+
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    sock.write_all(head.as_bytes()).await?;
+    sock.write_all(body.as_bytes()).await?;
+    sock.shutdown().await
+}
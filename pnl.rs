@@ -0,0 +1,378 @@
+// src/pnl.rs
+use anyhow::{anyhow, Result};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::{UiTransactionEncoding, UiTransactionTokenBalance};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::accounts::WSOL_MINT;
+use crate::amount::SolRate;
+use crate::rpc::RpcRotator;
+
+/// getTransaction poate întoarce null imediat după confirmare -> câteva reîncercări
+const FETCH_ATTEMPTS: u32 = 5;
+const FETCH_RETRY_MS: u64 = 2_000;
+
+/// Diferențele de balanță ale wallet-ului într-o tranzacție.
+#[derive(Debug, Clone, Default)]
+pub struct TxDelta {
+    pub fee_lamports: u64,
+    /// SOL nativ (fee-ul și tip-urile sunt deja scăzute aici)
+    pub sol_delta: i128,
+    /// raw per mint, doar conturile deținute de wallet
+    pub token_deltas: HashMap<String, i128>,
+}
+
+impl TxDelta {
+    fn merge(&mut self, other: &TxDelta) {
+        self.fee_lamports += other.fee_lamports;
+        self.sol_delta += other.sol_delta;
+        for (mint, d) in &other.token_deltas {
+            *self.token_deltas.entry(mint.clone()).or_insert(0) += d;
+        }
+    }
+
+    /// Câștigul net în unitățile raw ale lui `base_mint` (SOL = nativ + wSOL). Pentru alt base,
+    /// SOL-ul nativ (fee, tip, rent) intră convertit cu `rate`; fără curs rămâne doar tokenul.
+    pub fn realized_in(&self, base_mint: &str, rate: Option<&SolRate>) -> i128 {
+        let tok = self.token_deltas.get(base_mint).copied().unwrap_or(0);
+        if base_mint == WSOL_MINT {
+            return self.sol_delta + tok;
+        }
+        match rate {
+            Some(r) => tok + (self.sol_delta as f64 * r.raw_per_lamport).round() as i128,
+            None => tok,
+        }
+    }
+}
+
+/// PnL agregat pe o cheie (pereche "SYM→SYM" sau rută/label de bundle).
+#[derive(Debug, Clone, Default)]
+pub struct PnlEntry {
+    pub base_mint: String,
+    pub trades: u64,
+    /// tx-uri aterizate dar eșuate on-chain (au costat doar fee)
+    pub failed: u64,
+    /// estimat din quote-uri, raw în base_mint
+    pub estimated: i128,
+    /// din balanțele on-chain, raw în base_mint (după fee și tip)
+    pub realized: i128,
+    pub fees_lamports: u64,
+    pub tips_lamports: u64,
+}
+
+impl PnlEntry {
+    /// cât am pierdut (sau câștigat) față de estimare
+    pub fn slippage(&self) -> i128 {
+        self.realized - self.estimated
+    }
+}
+
+/// Ce știm despre un trade în momentul trimiterii.
+#[derive(Debug, Clone)]
+pub struct TradeCtx {
+    /// pereche "SYM→SYM" sau label de bundle/rută
+    pub key: String,
+    /// mint-ul în care se măsoară PnL-ul (inputul ciclului)
+    pub base_mint: String,
+    /// PnL estimat din quote-uri, raw în base_mint
+    pub estimated: i128,
+    pub tip_lamports: u64,
+    /// cursul base_mint/SOL, ca fee-ul și tip-ul (în lamports) să se scadă din PnL-ul realizat;
+    /// irelevant când base e SOL
+    pub sol_rate: Option<SolRate>,
+    /// pentru trade-uri pe un singur picior (A→B): tokenul primit se evaluează în base
+    pub mark_to: Option<MarkPrice>,
+}
+
+/// Cursul (din quote-ul invers) la care evaluăm un token ținut după trade.
+#[derive(Debug, Clone)]
+pub struct MarkPrice {
+    pub mint: String,
+    /// unități raw de base pentru o unitate raw din `mint`
+    pub base_per_unit: f64,
+}
+
+/// Poziția ținută într-un token, cu costul mediu de achiziție în `base_mint`.
+/// Se actualizează din tx-urile executate (PnlLedger::record), nu din quote-uri.
+#[derive(Debug, Clone)]
+pub struct Position {
+    pub base_mint: String,
+    /// raw în tokenul ținut
+    pub qty: u128,
+    /// raw în base_mint, cu fee-urile plătite la cumpărare
+    pub cost: u128,
+    /// prima achiziție din poziția curentă
+    pub opened: Instant,
+}
+
+impl Position {
+    /// unități raw de base pe unitate raw de token
+    pub fn cost_per_unit(&self) -> f64 {
+        if self.qty == 0 {
+            return 0.0;
+        }
+        self.cost as f64 / self.qty as f64
+    }
+
+    /// costul a `amount` unități la media curentă
+    pub fn cost_of(&self, amount: u64) -> u64 {
+        (amount as f64 * self.cost_per_unit()).ceil().min(u64::MAX as f64) as u64
+    }
+}
+
+#[derive(Default)]
+pub struct PnlLedger {
+    entries: Mutex<BTreeMap<String, PnlEntry>>,
+    /// mint -> poziție deschisă (cost basis)
+    positions: Mutex<HashMap<String, Position>>,
+}
+
+impl PnlLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Citește toate tx-urile (ex. picioarele unui bundle + tip) și înregistrează PnL-ul realizat
+    /// față de cel estimat. `landed=false` = tx eșuat on-chain (estimarea nu se adună).
+    pub async fn record(
+        &self,
+        rpcs: &RpcRotator,
+        owner: &Pubkey,
+        ctx: &TradeCtx,
+        sigs: &[Signature],
+        landed: bool,
+    ) -> Result<PnlEntry> {
+        let mut total = TxDelta::default();
+        for sig in sigs {
+            total.merge(&fetch_delta(rpcs, sig, owner).await?);
+        }
+        let mut realized = total.realized_in(&ctx.base_mint, ctx.sol_rate.as_ref());
+        if let Some(m) = &ctx.mark_to {
+            let held = total.token_deltas.get(&m.mint).copied().unwrap_or(0);
+            realized += (held as f64 * m.base_per_unit).round() as i128;
+        }
+        // un tx eșuat nu realizează estimarea și nu plătește tip (bundle-ul e atomic)
+        let (estimated, tip) = if landed { (ctx.estimated, ctx.tip_lamports) } else { (0, 0) };
+
+        diag!(
+            "[PNL] {} est={} realized={} slip={} fee={} tip={}",
+            ctx.key, estimated, realized, realized - estimated, total.fee_lamports, tip
+        );
+
+        if landed {
+            self.apply_basis(&ctx.base_mint, ctx.sol_rate.as_ref(), &total);
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        let e = entries.entry(ctx.key.clone()).or_default();
+        e.base_mint = ctx.base_mint.clone();
+        if landed {
+            e.trades += 1;
+        } else {
+            e.failed += 1;
+        }
+        e.estimated += estimated;
+        e.realized += realized;
+        e.fees_lamports += total.fee_lamports;
+        e.tips_lamports += tip;
+        Ok(e.clone())
+    }
+
+    pub fn position(&self, mint: &str) -> Option<Position> {
+        self.positions.lock().unwrap().get(mint).cloned()
+    }
+
+    /// Cumpărare (token +, base −) sau vânzare (token −) a exact unui token față de base.
+    /// Ciclurile (bundle-uri) se întorc în base și nu schimbă pozițiile.
+    fn apply_basis(&self, base_mint: &str, rate: Option<&SolRate>, delta: &TxDelta) {
+        let moved: Vec<(&String, i128)> = delta
+            .token_deltas
+            .iter()
+            .filter(|(m, _)| m.as_str() != base_mint && m.as_str() != WSOL_MINT)
+            .map(|(m, d)| (m, *d))
+            .collect();
+        let &[(mint, d)] = moved.as_slice() else {
+            return;
+        };
+        let base_delta = delta.realized_in(base_mint, rate);
+
+        let mut positions = self.positions.lock().unwrap();
+        if d > 0 && base_delta < 0 {
+            let p = positions.entry(mint.clone()).or_insert_with(|| Position {
+                base_mint: base_mint.to_string(),
+                qty: 0,
+                cost: 0,
+                opened: Instant::now(),
+            });
+            if p.base_mint != base_mint {
+                // cost în altă monedă: nu le putem aduna, pornim o poziție nouă
+                *p = Position { base_mint: base_mint.to_string(), qty: 0, cost: 0, opened: Instant::now() };
+            }
+            p.qty += d as u128;
+            p.cost += (-base_delta) as u128;
+            diag!("[BASIS] {} +{} cost={} ({} per unit)", mint, d, -base_delta, p.cost_per_unit());
+        } else if d < 0 {
+            let Some(p) = positions.get_mut(mint) else {
+                return;
+            };
+            let sold = ((-d) as u128).min(p.qty);
+            p.cost -= p.cost * sold / p.qty.max(1);
+            p.qty -= sold;
+            if p.qty == 0 {
+                positions.remove(mint);
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, PnlEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    pub fn render(&self) -> String {
+        let mut s = String::new();
+        s.push_str("\n-- Realized PnL (raw base units) --\n");
+        for (k, e) in self.snapshot() {
+            s.push_str(&format!(
+                "{k}: trades={} failed={} est={} realized={} slip={} fees={} tips={}\n",
+                e.trades, e.failed, e.estimated, e.realized, e.slippage(), e.fees_lamports, e.tips_lamports
+            ));
+        }
+        s
+    }
+}
+
+/// getTransaction + diferențele pre/post pentru `owner`. Wallet-ul e fee payer, deci index 0.
+pub async fn fetch_delta(rpcs: &RpcRotator, sig: &Signature, owner: &Pubkey) -> Result<TxDelta> {
+    let mut last_err = anyhow!("getTransaction {}: not found", sig);
+
+    for _ in 0..FETCH_ATTEMPTS {
+        let res = rpcs
+            .call(|c| async move {
+                c.get_transaction_with_config(
+                    sig,
+                    RpcTransactionConfig {
+                        encoding: Some(UiTransactionEncoding::Base64),
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        max_supported_transaction_version: Some(0),
+                    },
+                )
+                .await
+            })
+            .await;
+
+        match res {
+            Ok(tx) => {
+                let meta = tx
+                    .transaction
+                    .meta
+                    .ok_or_else(|| anyhow!("getTransaction {}: missing meta", sig))?;
+
+                let pre_sol = *meta.pre_balances.first().unwrap_or(&0) as i128;
+                let post_sol = *meta.post_balances.first().unwrap_or(&0) as i128;
+
+                let owner_s = owner.to_string();
+                let pre: Option<Vec<UiTransactionTokenBalance>> = meta.pre_token_balances.into();
+                let post: Option<Vec<UiTransactionTokenBalance>> = meta.post_token_balances.into();
+
+                let mut token_deltas: HashMap<String, i128> = HashMap::new();
+                let mut add = |balances: Option<Vec<UiTransactionTokenBalance>>, sign: i128| {
+                    for tb in balances.unwrap_or_default() {
+                        let tb_owner: Option<String> = tb.owner.into();
+                        if tb_owner.as_deref() != Some(owner_s.as_str()) {
+                            continue;
+                        }
+                        let amt = tb.ui_token_amount.amount.parse::<i128>().unwrap_or(0);
+                        *token_deltas.entry(tb.mint).or_insert(0) += sign * amt;
+                    }
+                };
+                add(pre, -1);
+                add(post, 1);
+                token_deltas.retain(|_, d| *d != 0);
+
+                return Ok(TxDelta {
+                    fee_lamports: meta.fee,
+                    sol_delta: post_sol - pre_sol,
+                    token_deltas,
+                });
+            }
+            Err(e) => last_err = e,
+        }
+        tokio::time::sleep(Duration::from_millis(FETCH_RETRY_MS)).await;
+    }
+
+    Err(last_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn delta(sol: i128, tokens: &[(&str, i128)]) -> TxDelta {
+        TxDelta {
+            fee_lamports: 5_000,
+            sol_delta: sol,
+            token_deltas: tokens.iter().map(|(m, d)| (m.to_string(), *d)).collect(),
+        }
+    }
+
+    #[test]
+    fn buy_opens_position_at_sol_cost() {
+        let l = PnlLedger::new();
+        l.apply_basis(WSOL_MINT, None, &delta(-2_005_000, &[(TOKEN, 1_000)]));
+        let p = l.position(TOKEN).unwrap();
+        assert_eq!((p.qty, p.cost), (1_000, 2_005_000));
+        assert_eq!(p.base_mint, WSOL_MINT);
+    }
+
+    #[test]
+    fn sells_reduce_cost_pro_rata_and_close_the_position() {
+        let l = PnlLedger::new();
+        l.apply_basis(WSOL_MINT, None, &delta(-2_000_000, &[(TOKEN, 1_000)]));
+        l.apply_basis(WSOL_MINT, None, &delta(1_200_000, &[(TOKEN, -250)]));
+        let p = l.position(TOKEN).unwrap();
+        assert_eq!((p.qty, p.cost), (750, 1_500_000));
+
+        l.apply_basis(WSOL_MINT, None, &delta(3_000_000, &[(TOKEN, -750)]));
+        assert!(l.position(TOKEN).is_none());
+    }
+
+    #[test]
+    fn cycles_back_to_base_leave_positions_alone() {
+        let l = PnlLedger::new();
+        l.apply_basis(WSOL_MINT, None, &delta(15_000, &[]));
+        l.apply_basis(USDC, None, &delta(-5_000, &[(USDC, 120)]));
+        assert!(l.positions.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn non_sol_base_cost_includes_converted_fee() {
+        let l = PnlLedger::new();
+        // 1 lamport = 0.01 raw USDC: 5000 lamports fee -> 50 raw
+        let rate = SolRate { mint: USDC.to_string(), decimals: 6, raw_per_lamport: 0.01 };
+        l.apply_basis(USDC, Some(&rate), &delta(-5_000, &[(USDC, -1_000), (TOKEN, 10)]));
+        let p = l.position(TOKEN).unwrap();
+        assert_eq!((p.qty, p.cost), (10, 1_050));
+
+        // fără curs rămâne doar tokenul de bază
+        let l = PnlLedger::new();
+        l.apply_basis(USDC, None, &delta(-5_000, &[(USDC, -1_000), (TOKEN, 10)]));
+        assert_eq!(l.position(TOKEN).unwrap().cost, 1_000);
+    }
+
+    #[test]
+    fn buy_in_another_base_restarts_the_position() {
+        let l = PnlLedger::new();
+        l.apply_basis(WSOL_MINT, None, &delta(-2_000_000, &[(TOKEN, 1_000)]));
+        l.apply_basis(USDC, None, &delta(-5_000, &[(USDC, -300), (TOKEN, 100)]));
+        let p = l.position(TOKEN).unwrap();
+        assert_eq!((p.base_mint.as_str(), p.qty, p.cost), (USDC, 100, 300));
+    }
+}
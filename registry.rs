@@ -0,0 +1,223 @@
+// src/registry.rs
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::accounts::TOKENS;
+use crate::config::Config;
+use crate::resolver::Resolver;
+use crate::rpc::RpcRotator;
+
+/// offset-ul câmpului `decimals` în layout-ul SPL Mint (la fel în Token-2022)
+const MINT_DECIMALS_OFFSET: usize = 44;
+/// getMultipleAccounts acceptă max 100 de chei
+const MULTIPLE_ACCOUNTS_CHUNK: usize = 100;
+
+/// De unde vine un token; la conflict câștigă sursa mai mare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TokenSource {
+    /// lista Jupiter (mii de tokeni, simboluri duplicate)
+    Jupiter,
+    /// accounts::TOKENS
+    Builtin,
+    /// `tokens` din config.json
+    Config,
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenEntry {
+    pub mint: String,
+    pub symbol: String,
+    pub decimals: Option<u8>,
+    pub source: TokenSource,
+}
+
+#[derive(Default)]
+struct Inner {
+    by_mint: HashMap<String, TokenEntry>,
+    /// simbol uppercase -> mint
+    by_symbol: HashMap<String, String>,
+}
+
+/// Tokenii cunoscuți de bot, indexați după mint, cu lookup după simbol.
+#[derive(Default)]
+pub struct TokenRegistry {
+    inner: RwLock<Inner>,
+}
+
+static GLOBAL: Lazy<TokenRegistry> = Lazy::new(|| {
+    let r = TokenRegistry::default();
+    for (sym, mint) in TOKENS.iter() {
+        r.insert(mint, sym, None, TokenSource::Builtin);
+    }
+    r
+});
+
+/// Registry-ul procesului (pornește cu accounts::TOKENS).
+pub fn global() -> &'static TokenRegistry {
+    &GLOBAL
+}
+
+impl TokenRegistry {
+    fn insert(&self, mint: &str, symbol: &str, decimals: Option<u8>, source: TokenSource) {
+        let mut inner = self.inner.write().unwrap();
+        let Inner { by_mint, by_symbol } = &mut *inner;
+
+        let e = by_mint.entry(mint.to_string()).or_insert_with(|| TokenEntry {
+            mint: mint.to_string(),
+            symbol: symbol.to_string(),
+            decimals,
+            source,
+        });
+        if source > e.source {
+            // redenumit: simbolul vechi nu mai duce la acest mint
+            let old_key = e.symbol.to_uppercase();
+            if old_key != symbol.to_uppercase() && by_symbol.get(&old_key) == Some(&e.mint) {
+                by_symbol.remove(&old_key);
+            }
+            e.symbol = symbol.to_string();
+            e.source = source;
+        }
+        if e.decimals.is_none() {
+            e.decimals = decimals;
+        }
+        // o sursă mai slabă nu adaugă alias-uri pentru un mint deja numit altfel
+        if e.symbol != symbol {
+            return;
+        }
+        let source = e.source;
+
+        // simbolul e păstrat de sursa cu prioritate mai mare; la egalitate rămâne primul
+        let key = symbol.to_uppercase();
+        let keep_old = by_symbol
+            .get(&key)
+            .and_then(|m| by_mint.get(m))
+            .map(|old| old.mint != mint && old.source >= source)
+            .unwrap_or(false);
+        if !keep_old {
+            by_symbol.insert(key, mint.to_string());
+        }
+    }
+
+    /// `tokens` din config: { "USDC": "EPjF..." }. Apelat și la hot-reload.
+    pub fn load_config(&self, cfg: &Config) -> usize {
+        let Some(tokens) = &cfg.tokens else {
+            return 0;
+        };
+        let mut n = 0;
+        for (sym, v) in tokens {
+            if let Some(mint) = v.as_str() {
+                self.insert(mint, sym, None, TokenSource::Config);
+                n += 1;
+            }
+        }
+        n
+    }
+
+    /// Copia locală a listei Jupiter (din Resolver) — simboluri și decimals.
+    pub fn merge_jupiter(&self, resolver: &Resolver) -> usize {
+        let mut n = 0;
+        for t in resolver.tokens() {
+            self.insert(&t.address, &t.symbol, Some(t.decimals), TokenSource::Jupiter);
+            n += 1;
+        }
+        n
+    }
+
+    /// Citește decimals on-chain (getMultipleAccounts) pentru tokenii urmăriți fără decimals.
+    pub async fn fetch_missing_decimals(&self, rpcs: &RpcRotator) -> Result<usize> {
+        let missing: Vec<Pubkey> = self
+            .tracked()
+            .into_iter()
+            .filter(|t| t.decimals.is_none())
+            .filter_map(|t| t.mint.parse().ok())
+            .collect();
+
+        let mut n = 0;
+        for chunk in missing.chunks(MULTIPLE_ACCOUNTS_CHUNK) {
+            let accounts = rpcs.call(|c| async move { c.get_multiple_accounts(chunk).await }).await?;
+            let mut inner = self.inner.write().unwrap();
+            for (pk, acc) in chunk.iter().zip(accounts) {
+                let Some(d) = acc.and_then(|a| a.data.get(MINT_DECIMALS_OFFSET).copied()) else {
+                    eprintln!("[TOKENS] {} is not a mint account", pk);
+                    continue;
+                };
+                if let Some(e) = inner.by_mint.get_mut(&pk.to_string()) {
+                    e.decimals = Some(d);
+                    n += 1;
+                }
+            }
+        }
+        Ok(n)
+    }
+
+    /// Decimals pentru orice mint; dacă nu e cunoscut, se citește on-chain și se ține minte.
+    pub async fn decimals(&self, rpcs: &RpcRotator, mint: &str) -> Result<u8> {
+        if let Some(d) = self.get(mint).and_then(|t| t.decimals) {
+            return Ok(d);
+        }
+        let pk = mint.parse::<Pubkey>()?;
+        let pk = &pk;
+        let supply = rpcs.call(|c| async move { c.get_token_supply(pk).await }).await?;
+
+        let mut inner = self.inner.write().unwrap();
+        if let Some(e) = inner.by_mint.get_mut(mint) {
+            e.decimals = Some(supply.decimals);
+        }
+        Ok(supply.decimals)
+    }
+
+    pub fn get(&self, mint: &str) -> Option<TokenEntry> {
+        self.inner.read().unwrap().by_mint.get(mint).cloned()
+    }
+
+    pub fn symbol_for_mint(&self, mint: &str) -> Option<String> {
+        self.get(mint).map(|t| t.symbol)
+    }
+
+    pub fn by_symbol(&self, symbol: &str) -> Option<TokenEntry> {
+        let inner = self.inner.read().unwrap();
+        inner
+            .by_symbol
+            .get(&symbol.to_uppercase())
+            .and_then(|m| inner.by_mint.get(m))
+            .cloned()
+    }
+
+    /// simbol sau mint -> intrare; mint-urile necunoscute se acceptă fără simbol
+    pub fn resolve(&self, symbol_or_mint: &str) -> Result<TokenEntry> {
+        if let Some(t) = self.get(symbol_or_mint).or_else(|| self.by_symbol(symbol_or_mint)) {
+            return Ok(t);
+        }
+        if symbol_or_mint.parse::<Pubkey>().is_ok() {
+            return Ok(TokenEntry {
+                mint: symbol_or_mint.to_string(),
+                symbol: symbol_or_mint.to_string(),
+                decimals: None,
+                source: TokenSource::Jupiter,
+            });
+        }
+        Err(anyhow!("Token not found: {}", symbol_or_mint))
+    }
+
+    /// Tokenii pe care îi urmărim activ (config + builtin), fără restul listei Jupiter.
+    pub fn tracked(&self) -> Vec<TokenEntry> {
+        let mut out: Vec<TokenEntry> = self
+            .inner
+            .read()
+            .unwrap()
+            .by_mint
+            .values()
+            .filter(|t| t.source >= TokenSource::Builtin)
+            .cloned()
+            .collect();
+        out.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        out
+    }
+
+    pub fn count(&self) -> usize {
+        self.inner.read().unwrap().by_mint.len()
+    }
+}
@@ -0,0 +1,174 @@
+// src/reload.rs
+use anyhow::Result;
+use serde_json::Value;
+use std::fs;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use crate::config::Config;
+
+/// Secțiuni citite o singură dată la pornire (clienți construiți în main) —
+/// o schimbare aici se aplică doar după restart.
+const RESTART_ONLY: &[&str] = &[
+    "$.wallet_keypair_path",
+    "$.jupiter_base",
+    "$.prefer_orca",
+    "$.max_slippage_bps",
+    "$.quote_cache",
+    "$.resolver",
+    "$.rpcs",
+    "$.ws_rpcs",
+    "$.rpc_config",
+    "$.jito",
+    "$.reload",
+    "$.logs",
+    "$.metrics",
+    "$.account_setup",
+];
+
+/// Config-ul curent, înlocuit atomic la reload. Consumatorii iau `get()` la fiecare trecere.
+pub struct SharedConfig {
+    path: String,
+    current: RwLock<Arc<Config>>,
+    /// `--dry-run` din CLI: rămâne activ și după reload
+    force_dry_run: bool,
+}
+
+impl SharedConfig {
+    pub fn new(path: &str, cfg: Config) -> Self {
+        Self {
+            path: path.to_string(),
+            current: RwLock::new(Arc::new(cfg)),
+            force_dry_run: false,
+        }
+    }
+
+    pub fn force_dry_run(mut self, on: bool) -> Self {
+        self.force_dry_run = on;
+        self
+    }
+
+    pub fn get(&self) -> Arc<Config> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Re-citește și validează fișierul. La eroare config-ul vechi rămâne activ.
+    /// Secțiunile din RESTART_ONLY păstrează valoarea de la pornire (diff-ul le arată totuși).
+    /// Întoarce liniile de diff (goale = nimic schimbat).
+    pub fn reload(&self) -> Result<Vec<String>> {
+        let mut new_cfg = Config::load_from_file(&self.path)?;
+        if self.force_dry_run {
+            new_cfg.dry_run = true;
+        }
+        let old_cfg = self.get();
+
+        let old_v = serde_json::to_value(old_cfg.as_ref())?;
+        let mut new_v = serde_json::to_value(&new_cfg)?;
+        let mut changes = Vec::new();
+        diff_values(&old_v, &new_v, "$", &mut changes);
+
+        if !changes.is_empty() {
+            keep_restart_only(&old_v, &mut new_v);
+            let new_cfg: Config = serde_json::from_value(new_v)?;
+            crate::registry::global().load_config(&new_cfg);
+            *self.current.write().unwrap() = Arc::new(new_cfg);
+        }
+        Ok(changes)
+    }
+
+    /// Verifică mtime-ul fișierului la fiecare `every_ms` și face reload când se schimbă.
+    pub fn spawn_watcher(self: &Arc<Self>, every_ms: u64) {
+        let me = self.clone();
+        tokio::spawn(async move {
+            let mut last = mtime(&me.path);
+            loop {
+                tokio::time::sleep(Duration::from_millis(every_ms.max(250))).await;
+                let now = mtime(&me.path);
+                if now.is_none() || now == last {
+                    continue;
+                }
+                // ținem minte mtime-ul și la eșec: reîncercăm doar la următoarea salvare
+                last = now;
+
+                match me.reload() {
+                    Ok(changes) if changes.is_empty() => {
+                        diag!("[CONFIG] {} touched, no effective changes", me.path);
+                    }
+                    Ok(changes) => {
+                        diag!("[CONFIG] reloaded {} ({} change(s)):", me.path, changes.len());
+                        for c in &changes {
+                            diag!("  {}", c);
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("[CONFIG] reload rejected, keeping previous config: {:#}", e);
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Copiază în `new` valorile din `old` pentru căile RESTART_ONLY (toate de forma `$.cheie`).
+fn keep_restart_only(old: &Value, new: &mut Value) {
+    let Value::Object(new) = new else { return };
+    for key in RESTART_ONLY.iter().filter_map(|p| p.strip_prefix("$.")) {
+        match old.get(key) {
+            Some(v) => {
+                new.insert(key.to_string(), v.clone());
+            }
+            None => {
+                new.remove(key);
+            }
+        }
+    }
+}
+
+fn mtime(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn diff_values(old: &Value, new: &Value, path: &str, out: &mut Vec<String>) {
+    if old == new {
+        return;
+    }
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (k, av) in a {
+                let child = format!("{path}.{k}");
+                match b.get(k) {
+                    Some(bv) => diff_values(av, bv, &child, out),
+                    None => out.push(line(&child, &short(av), "-")),
+                }
+            }
+            for (k, bv) in b {
+                if !a.contains_key(k) {
+                    out.push(line(&format!("{path}.{k}"), "-", &short(bv)));
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+            for (i, (av, bv)) in a.iter().zip(b.iter()).enumerate() {
+                diff_values(av, bv, &format!("{path}[{i}]"), out);
+            }
+        }
+        _ => out.push(line(path, &short(old), &short(new))),
+    }
+}
+
+fn line(path: &str, old: &str, new: &str) -> String {
+    let restart = RESTART_ONLY.iter().any(|p| path.starts_with(p));
+    format!(
+        "{path}: {old} -> {new}{}",
+        if restart { " (restart required)" } else { "" }
+    )
+}
+
+fn short(v: &Value) -> String {
+    let s = v.to_string();
+    if s.chars().count() > 120 {
+        format!("{}…", s.chars().take(120).collect::<String>())
+    } else {
+        s
+    }
+}
@@ -1,37 +1,217 @@
 use anyhow::{Result, anyhow};
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
-#[derive(Debug, Deserialize)]
+use crate::config::ResolverConfig;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenInfo {
     pub address: String,
     pub symbol: String,
     pub decimals: u8,
+    /// ex. "verified", "strict", "community" (lista Jupiter)
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl TokenInfo {
+    fn verified(&self) -> bool {
+        self.tags.iter().any(|t| t == "verified" || t == "strict")
+    }
+}
+
+#[derive(Default)]
+struct Index {
+    by_mint: HashMap<String, TokenInfo>,
+    /// simbol uppercase -> mint-ul ales dintre duplicate
+    by_symbol: HashMap<String, String>,
 }
 
+/// Lista de tokeni Jupiter: se încarcă din snapshot-ul local, se reîmprospătează în fundal.
 pub struct Resolver {
     client: Client,
-    tokens: HashMap<String, TokenInfo>,
+    url: String,
+    snapshot_path: String,
+    /// simbol uppercase -> mint fixat în config (câștigă la simboluri duplicate)
+    pinned: HashMap<String, String>,
+    index: RwLock<Index>,
 }
 
 impl Resolver {
-    pub async fn new() -> Result<Self> {
-        let url = "https://token.jup.ag/all";
-        let client = Client::new();
-        let resp: Vec<TokenInfo> = client.get(url).send().await?.json().await?;
-        
-        let mut tokens = HashMap::new();
-        for t in resp {
-            tokens.insert(t.symbol.to_uppercase(), t);
+    /// Doar din snapshot (fără rețea). Dacă fișierul lipsește, resolver-ul pornește gol.
+    pub fn load(cfg: &ResolverConfig, pinned: HashMap<String, String>) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_millis(cfg.timeout_ms))
+            .build()
+            .unwrap_or_default();
+        let me = Self {
+            client,
+            url: cfg.url.clone(),
+            snapshot_path: cfg.snapshot_path.clone(),
+            pinned: pinned.into_iter().map(|(s, m)| (s.to_uppercase(), m)).collect(),
+            index: RwLock::new(Index::default()),
+        };
+
+        match std::fs::read_to_string(&me.snapshot_path) {
+            Ok(txt) => match serde_json::from_str::<Vec<TokenInfo>>(&txt) {
+                Ok(list) => {
+                    me.rebuild(list);
+                    diag!("[RESOLVER] loaded {} tokens from {}", me.len(), me.snapshot_path);
+                }
+                Err(e) => eprintln!("[RESOLVER] snapshot {} unreadable: {}", me.snapshot_path, e),
+            },
+            Err(_) => diag!("[RESOLVER] no snapshot at {}", me.snapshot_path),
+        }
+        me
+    }
+
+    /// Descarcă lista de la `url`, reface indexul și rescrie snapshot-ul.
+    pub async fn refresh(&self) -> Result<usize> {
+        let list: Vec<TokenInfo> = self
+            .client
+            .get(&self.url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if list.is_empty() {
+            return Err(anyhow!("{}: empty token list", self.url));
         }
 
-        Ok(Self { client, tokens })
+        self.write_snapshot(&list)?;
+        let n = list.len();
+        self.rebuild(list);
+        Ok(n)
+    }
+
+    /// Refresh periodic; după fiecare reușită lista intră și în TokenRegistry.
+    pub fn spawn_refresh(self: &Arc<Self>, every_ms: u64) {
+        let me = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(every_ms.max(60_000))).await;
+                match me.refresh().await {
+                    Ok(n) => {
+                        crate::registry::global().merge_jupiter(&me);
+                        diag!("[RESOLVER] refreshed {} tokens from {}", n, me.url);
+                    }
+                    Err(e) => eprintln!("[RESOLVER] refresh failed, keeping snapshot: {:#}", e),
+                }
+            }
+        });
+    }
+
+    /// rezolvă simbol (ex. "SOL") sau mint -> TokenInfo; simbolul are prioritate
+    pub fn resolve(&self, symbol_or_mint: &str) -> Result<TokenInfo> {
+        let chosen = self.index.read().unwrap().by_symbol.get(&symbol_or_mint.to_uppercase()).cloned();
+        chosen
+            .and_then(|m| self.by_mint(&m))
+            .or_else(|| self.by_mint(symbol_or_mint))
+            .ok_or_else(|| anyhow!("Token not found: {}", symbol_or_mint))
+    }
+
+    /// Căutare după mint; găsește și duplicatele care nu au fost alese pentru simbolul lor.
+    pub fn by_mint(&self, mint: &str) -> Option<TokenInfo> {
+        self.index.read().unwrap().by_mint.get(mint).cloned()
+    }
+
+    /// Toți tokenii; mint-ul ales pentru fiecare simbol vine înaintea duplicatelor.
+    pub fn tokens(&self) -> Vec<TokenInfo> {
+        let idx = self.index.read().unwrap();
+        let mut out: Vec<TokenInfo> = idx.by_mint.values().cloned().collect();
+        let chosen = |t: &TokenInfo| idx.by_symbol.get(&t.symbol.to_uppercase()) == Some(&t.address);
+        out.sort_by_key(|t| !chosen(t));
+        out
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.read().unwrap().by_mint.len()
     }
 
-    /// rezolvă simbol (ex. "SOL") -> mint address
-    pub fn resolve(&self, symbol: &str) -> Result<&TokenInfo> {
-        self.tokens.get(&symbol.to_uppercase())
-            .ok_or_else(|| anyhow!("Token not found: {}", symbol))
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
-}
\ No newline at end of file
+
+    /// Simboluri duplicate: mint fixat în config > verified/strict > primul din listă.
+    fn rebuild(&self, list: Vec<TokenInfo>) {
+        let mut idx = Index::default();
+        for t in list {
+            let key = t.symbol.to_uppercase();
+            let take = match idx.by_symbol.get(&key).and_then(|m| idx.by_mint.get(m)) {
+                None => true,
+                Some(cur) => {
+                    let pinned = self.pinned.get(&key);
+                    if pinned == Some(&t.address) {
+                        true
+                    } else if pinned == Some(&cur.address) {
+                        false
+                    } else {
+                        t.verified() && !cur.verified()
+                    }
+                }
+            };
+            if take {
+                idx.by_symbol.insert(key, t.address.clone());
+            }
+            idx.by_mint.insert(t.address.clone(), t);
+        }
+        *self.index.write().unwrap() = idx;
+    }
+
+    /// scriere atomică: fișier temporar + rename
+    fn write_snapshot(&self, list: &[TokenInfo]) -> Result<()> {
+        let path = Path::new(&self.snapshot_path);
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(list)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+    const FAKE_BONK: &str = "BonkFake1111111111111111111111111111111111";
+
+    fn token(address: &str, symbol: &str, tags: &[&str]) -> TokenInfo {
+        TokenInfo {
+            address: address.into(),
+            symbol: symbol.into(),
+            decimals: 5,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    fn resolver(list: Vec<TokenInfo>) -> Resolver {
+        let cfg = ResolverConfig { snapshot_path: "cache/__missing__.json".into(), ..Default::default() };
+        let r = Resolver::load(&cfg, HashMap::new());
+        r.rebuild(list);
+        r
+    }
+
+    #[test]
+    fn by_mint_finds_chosen_and_shadowed_duplicates() {
+        let r = resolver(vec![token(FAKE_BONK, "BONK", &[]), token(BONK, "Bonk", &["verified"])]);
+        assert_eq!(r.resolve("bonk").unwrap().address, BONK);
+        assert_eq!(r.by_mint(BONK).unwrap().symbol, "Bonk");
+        assert_eq!(r.by_mint(FAKE_BONK).unwrap().symbol, "BONK");
+        assert!(r.by_mint("So11111111111111111111111111111111111111112").is_none());
+    }
+
+    #[test]
+    fn resolve_accepts_a_mint() {
+        let r = resolver(vec![token(FAKE_BONK, "BONK", &[]), token(BONK, "BONK", &["strict"])]);
+        assert_eq!(r.resolve(FAKE_BONK).unwrap().address, FAKE_BONK);
+        assert!(r.resolve("WIF").is_err());
+    }
+}
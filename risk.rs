@@ -0,0 +1,129 @@
+// src/risk.rs
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use std::fmt;
+
+use crate::config::Config;
+use crate::events::{self, Event};
+use crate::jupiter::QuoteResponse;
+
+/// Calea de execuție pe care se aplică o limită.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TradePath {
+    /// `pairs` (handle_pair)
+    Pair,
+    /// auto-unwind și `unwind`
+    Unwind,
+    /// bundles.two_leg
+    TwoLeg,
+    /// bundles.three_leg și tri_scan
+    ThreeLeg,
+}
+
+impl TradePath {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TradePath::Pair => "pair",
+            TradePath::Unwind => "unwind",
+            TradePath::TwoLeg => "two_leg",
+            TradePath::ThreeLeg => "three_leg",
+        }
+    }
+}
+
+/// Limita de impact per picior, în bps: override (pereche/bundle) > `risk` > implicit.
+/// Pentru bundle-uri, fără `risk.*` rămâne `bundles.execution.price_impact_bps_limit`.
+pub fn max_impact_bps(cfg: &Config, path: TradePath, override_bps: Option<f64>) -> f64 {
+    if let Some(b) = override_bps {
+        return b;
+    }
+    let r = &cfg.risk;
+    let legacy = || {
+        cfg.bundles
+            .as_ref()
+            .map(|b| b.execution.price_impact_bps_limit)
+            .unwrap_or(r.pair_max_impact_bps)
+    };
+    match path {
+        TradePath::Pair => r.pair_max_impact_bps,
+        TradePath::Unwind => r.unwind_max_impact_bps,
+        TradePath::TwoLeg => r.two_leg_max_impact_bps.unwrap_or_else(legacy),
+        TradePath::ThreeLeg => r.three_leg_max_impact_bps.unwrap_or_else(legacy),
+    }
+}
+
+/// De ce n-a fost executat un ciclu / swap.
+#[derive(Debug, Clone)]
+pub enum Reject {
+    /// quote cu out=0 (picioarele numărate de la 1)
+    NoOutput { leg: usize },
+    /// impact peste limită
+    Impact { leg: usize, bps: f64, limit: f64 },
+    /// sizing: nicio mărime din căutare n-a trecut de limite
+    NoSize,
+    /// PnL estimat sub prag (unități raw ale bazei)
+    Profit { pnl: i128, thresh: i128 },
+    /// unwind: out sub minimul din cost basis + fee + marjă
+    MinOut { out: u64, min_out: u64 },
+}
+
+impl Reject {
+    /// cheia din statistici
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Reject::NoOutput { .. } => "no_output",
+            Reject::Impact { .. } => "impact",
+            Reject::NoSize => "no_size",
+            Reject::Profit { .. } => "profit",
+            Reject::MinOut { .. } => "min_out",
+        }
+    }
+}
+
+impl fmt::Display for Reject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reject::NoOutput { leg } => write!(f, "leg#{} out=0", leg),
+            Reject::Impact { leg, bps, limit } => write!(f, "leg#{} impact {:.2}bp > {}bp", leg, bps, limit),
+            Reject::NoSize => write!(f, "no trade size within risk limits"),
+            Reject::Profit { pnl, thresh } => write!(f, "pnl {} < {}", pnl, thresh),
+            Reject::MinOut { out, min_out } => write!(f, "out {} < min_out {}", out, min_out),
+        }
+    }
+}
+
+/// Verifică un picior (numerotat de la 1) față de limita de impact în bps.
+pub fn check_leg(q: &QuoteResponse, leg: usize, limit_bps: f64) -> Result<(), Reject> {
+    if q.out_amount == 0 {
+        return Err(Reject::NoOutput { leg });
+    }
+    let bps = q.price_impact_bps();
+    if bps > limit_bps {
+        return Err(Reject::Impact { leg, bps, limit: limit_bps });
+    }
+    Ok(())
+}
+
+/// Contoare globale (cale, motiv); Stats le copiază înainte de render, ca la QuoteCache.
+static REJECTS: Lazy<DashMap<(TradePath, &'static str), u64>> = Lazy::new(DashMap::new);
+
+/// Numără respingerea și o trece în jurnalul de evenimente (DecisionSkipped).
+pub fn record(path: TradePath, key: &str, r: &Reject) {
+    *REJECTS.entry((path, r.kind())).or_insert(0) += 1;
+    events::emit(Event::DecisionSkipped {
+        path: path.as_str(),
+        key: key.to_string(),
+        reason: r.kind(),
+        detail: r.to_string(),
+    });
+}
+
+/// "cale/motiv" -> număr, sortat după cheie
+pub fn rejections() -> Vec<(String, u64)> {
+    let mut out: Vec<(String, u64)> = REJECTS
+        .iter()
+        .map(|e| (format!("{}/{}", e.key().0.as_str(), e.key().1), *e.value()))
+        .collect();
+    out.sort();
+    out
+}
@@ -1,23 +1,157 @@
 use anyhow::{anyhow, Result};
-use solana_client::rpc_client::RpcClient;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use std::future::Future;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+use crate::config::RpcConfig;
+use crate::metrics;
+
+/// Pondere EWMA pentru latență (mai mare = reacționează mai repede).
+const LATENCY_ALPHA: f64 = 0.3;
+
+/// Starea de sănătate a unui endpoint din `cfg.rpcs`.
+#[derive(Debug, Clone)]
+pub struct EndpointHealth {
+    pub url: String,
+    /// EWMA latență (ms); None până la primul răspuns
+    pub latency_ms: Option<f64>,
+    pub ok_count: u64,
+    pub err_count: u64,
+    pub consecutive_errors: u32,
+    /// ultimul slot văzut prin getSlot (probe)
+    pub last_slot: Option<u64>,
+    /// cât timp endpoint-ul e scos din rotație după timeout/429/5xx
+    pub cooldown_until: Option<Instant>,
+}
+
+impl EndpointHealth {
+    fn new(url: String) -> Self {
+        Self {
+            url,
+            latency_ms: None,
+            ok_count: 0,
+            err_count: 0,
+            consecutive_errors: 0,
+            last_slot: None,
+            cooldown_until: None,
+        }
+    }
+
+    fn in_cooldown(&self, now: Instant) -> bool {
+        self.cooldown_until.map(|t| now < t).unwrap_or(false)
+    }
+
+    fn record_ok(&mut self, latency: Duration) {
+        let ms = latency.as_secs_f64() * 1000.0;
+        self.latency_ms = Some(match self.latency_ms {
+            Some(prev) => LATENCY_ALPHA * ms + (1.0 - LATENCY_ALPHA) * prev,
+            None => ms,
+        });
+        self.ok_count += 1;
+        self.consecutive_errors = 0;
+    }
+}
 
 pub struct RpcRotator {
     urls: Vec<String>,
+    /// host-ul fiecărui URL, pentru metrici (fără api-key)
+    labels: Vec<String>,
+    /// un client (async) per endpoint, reutilizat (pool HTTP comun)
+    clients: Vec<Arc<RpcClient>>,
     idx: Mutex<usize>,
     timeout_ms: u64,
+    health: Mutex<Vec<EndpointHealth>>,
+    cooldown: Duration,
+    max_slot_lag: u64,
 }
 
 impl RpcRotator {
-    pub fn new(urls: Vec<String>, timeout_ms: u64) -> Self {
-        Self { urls, idx: Mutex::new(0), timeout_ms }
+    pub fn new(urls: Vec<String>, rpc_cfg: &RpcConfig) -> Self {
+        let health = urls.iter().cloned().map(EndpointHealth::new).collect();
+        let clients = urls
+            .iter()
+            .map(|u| {
+                Arc::new(RpcClient::new_with_timeout(
+                    u.clone(),
+                    Duration::from_millis(rpc_cfg.timeout_ms),
+                ))
+            })
+            .collect();
+        let labels = urls.iter().map(|u| metrics::endpoint_label(u)).collect();
+        Self {
+            urls,
+            labels,
+            clients,
+            idx: Mutex::new(0),
+            timeout_ms: rpc_cfg.timeout_ms,
+            health: Mutex::new(health),
+            cooldown: Duration::from_millis(rpc_cfg.cooldown_ms),
+            max_slot_lag: rpc_cfg.max_slot_lag,
+        }
     }
 
-    pub fn client(&self) -> RpcClient {
-        let i = *self.idx.lock().unwrap();
-        let url = &self.urls[i % self.urls.len()];
-        RpcClient::new_with_timeout(url.clone(), Duration::from_millis(self.timeout_ms))
+    /// Rulează `f` pe cel mai bun endpoint de citire; la timeout/429/5xx trece pe următorul.
+    pub async fn call<T, E, F, Fut>(&self, f: F) -> Result<T>
+    where
+        F: FnMut(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = std::result::Result<T, E>>,
+        E: Into<anyhow::Error>,
+    {
+        self.call_with(false, f).await
+    }
+
+    /// Ca `call`, dar alege endpoint-ul cel mai proaspăt (blockhash, send).
+    pub async fn call_send<T, E, F, Fut>(&self, f: F) -> Result<T>
+    where
+        F: FnMut(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = std::result::Result<T, E>>,
+        E: Into<anyhow::Error>,
+    {
+        self.call_with(true, f).await
+    }
+
+    async fn call_with<T, E, F, Fut>(&self, for_send: bool, mut f: F) -> Result<T>
+    where
+        F: FnMut(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = std::result::Result<T, E>>,
+        E: Into<anyhow::Error>,
+    {
+        let mut tried: Vec<usize> = Vec::new();
+        let mut last_err = anyhow!("config.rpcs is empty");
+
+        while tried.len() < self.urls.len() {
+            let i = self.pick(for_send, &tried);
+            tried.push(i);
+            let client = self.client_at(i);
+
+            let t0 = Instant::now();
+            match f(client).await {
+                Ok(v) => {
+                    self.record_ok(i, t0.elapsed());
+                    metrics::observe("solpro_rpc_request_seconds", &[("endpoint", self.labels[i].as_str())], t0.elapsed().as_secs_f64());
+                    return Ok(v);
+                }
+                Err(e) => {
+                    let e: anyhow::Error = e.into();
+                    metrics::inc("solpro_rpc_errors_total", &[("endpoint", self.labels[i].as_str()), ("status", error_status(&e).as_str())]);
+                    let failover = e
+                        .downcast_ref::<ClientError>()
+                        .map(is_failover_error)
+                        .unwrap_or(false);
+                    if !failover {
+                        // eroare de aplicație (ex. cont inexistent) — alt endpoint nu ajută
+                        return Err(e);
+                    }
+                    self.record_failure(i, &e);
+                    self.rotate();
+                    last_err = e;
+                }
+            }
+        }
+
+        Err(last_err.context(format!("all {} RPC endpoints failed", self.urls.len())))
     }
 
     pub fn rotate(&self) {
@@ -36,6 +170,142 @@ impl RpcRotator {
         }
         Ok(())
     }
+
+    pub fn health_snapshot(&self) -> Vec<EndpointHealth> {
+        self.health.lock().unwrap().clone()
+    }
+
+    /// getSlot pe toate endpoint-urile: măsoară latența, slotul și scoate din cooldown
+    /// endpoint-urile care răspund din nou.
+    pub async fn probe_all(&self) {
+        for i in 0..self.urls.len() {
+            let client = self.client_at(i);
+            let t0 = Instant::now();
+            match client.get_slot().await {
+                Ok(slot) => {
+                    let mut h = self.health.lock().unwrap();
+                    let e = &mut h[i];
+                    e.record_ok(t0.elapsed());
+                    e.last_slot = Some(slot);
+                    if e.cooldown_until.take().is_some() {
+                        diag!("[RPC] {} back in rotation (slot {})", e.url, slot);
+                    }
+                }
+                Err(err) => {
+                    let err = anyhow::Error::from(err);
+                    self.record_failure(i, &err);
+                }
+            }
+        }
+    }
+
+    /// Task periodic de probe.
+    pub fn spawn_health_probe(self: &Arc<Self>, every_ms: u64) {
+        let me = self.clone();
+        tokio::spawn(async move {
+            loop {
+                me.probe_all().await;
+                tokio::time::sleep(Duration::from_millis(every_ms.max(1_000))).await;
+            }
+        });
+    }
+
+    fn client_at(&self, i: usize) -> Arc<RpcClient> {
+        self.clients[i % self.clients.len()].clone()
+    }
+
+    /// Alege un endpoint care nu e în cooldown și nu e în `exclude`.
+    /// Dacă toate sunt excluse/în cooldown, rămâne pe cel curent.
+    fn pick(&self, for_send: bool, exclude: &[usize]) -> usize {
+        let h = self.health.lock().unwrap();
+        let now = Instant::now();
+        let current = *self.idx.lock().unwrap() % self.urls.len().max(1);
+
+        let candidates: Vec<usize> = (0..h.len())
+            .filter(|i| !exclude.contains(i) && !h[*i].in_cooldown(now))
+            .collect();
+        if candidates.is_empty() {
+            return (0..h.len()).find(|i| !exclude.contains(i)).unwrap_or(current);
+        }
+
+        let max_slot = candidates.iter().filter_map(|&i| h[i].last_slot).max();
+        // latență necunoscută = timeout, ca endpoint-urile măsurate să aibă prioritate
+        let latency = |i: usize| h[i].latency_ms.unwrap_or(self.timeout_ms as f64);
+
+        let chosen = if for_send {
+            candidates
+                .iter()
+                .copied()
+                .max_by(|&a, &b| {
+                    h[a].last_slot
+                        .cmp(&h[b].last_slot)
+                        .then(latency(b).total_cmp(&latency(a)))
+                })
+                .unwrap_or(current)
+        } else {
+            let fresh: Vec<usize> = candidates
+                .iter()
+                .copied()
+                .filter(|&i| match (max_slot, h[i].last_slot) {
+                    (Some(max), Some(s)) => max.saturating_sub(s) <= self.max_slot_lag,
+                    _ => true,
+                })
+                .collect();
+            let pool = if fresh.is_empty() { &candidates } else { &fresh };
+            pool.iter()
+                .copied()
+                .min_by(|&a, &b| latency(a).total_cmp(&latency(b)).then((a != current).cmp(&(b != current))))
+                .unwrap_or(current)
+        };
+
+        drop(h);
+        *self.idx.lock().unwrap() = chosen;
+        chosen
+    }
+
+    fn record_ok(&self, i: usize, latency: Duration) {
+        self.health.lock().unwrap()[i].record_ok(latency);
+    }
+
+    fn record_failure(&self, i: usize, err: &anyhow::Error) {
+        let mut h = self.health.lock().unwrap();
+        let e = &mut h[i];
+        e.err_count += 1;
+        e.consecutive_errors += 1;
+        if !e.in_cooldown(Instant::now()) {
+            eprintln!(
+                "[RPC] {} failed ({}), cooldown {}ms",
+                e.url, err, self.cooldown.as_millis()
+            );
+        }
+        e.cooldown_until = Some(Instant::now() + self.cooldown);
+    }
+}
+
+/// Erori care țin de endpoint (timeout, conexiune, 429, 5xx) -> merită încercat altul.
+fn is_failover_error(e: &ClientError) -> bool {
+    match e.kind() {
+        ClientErrorKind::Io(_) => true,
+        ClientErrorKind::Reqwest(re) => {
+            re.is_timeout()
+                || re.is_connect()
+                || re
+                    .status()
+                    .map(|s| s.as_u16() == 429 || s.is_server_error())
+                    .unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+/// Eticheta de status pentru metrici: cod HTTP, timeout/connect/io sau "rpc" (eroare de aplicație).
+fn error_status(e: &anyhow::Error) -> String {
+    match e.downcast_ref::<ClientError>().map(|c| c.kind()) {
+        Some(ClientErrorKind::Reqwest(re)) => metrics::http_error_status(re),
+        Some(ClientErrorKind::Io(_)) => "io".into(),
+        Some(ClientErrorKind::RpcError(_)) => "rpc".into(),
+        _ => "other".into(),
+    }
 }
 
-pub type RpcRotatorRef = Arc<RpcRotator>;
\ No newline at end of file
+pub type RpcRotatorRef = Arc<RpcRotator>;
@@ -0,0 +1,126 @@
+// src/simulate.rs
+use anyhow::Result;
+use solana_client::rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::compute_budget::{self, ComputeBudgetInstruction};
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::{TransactionError, VersionedTransaction};
+
+use crate::rpc::RpcRotator;
+
+/// marjă peste CU măsurat, ca tx-ul să nu rămână fără compute pe alt slot
+const CU_MARGIN_PCT: u64 = 15;
+
+/// Rezultatul unui simulateTransaction.
+#[derive(Debug, Clone)]
+pub struct SimReport {
+    pub err: Option<TransactionError>,
+    pub units_consumed: Option<u64>,
+    pub logs: Vec<String>,
+    /// lamports după simulare pentru fiecare adresă cerută (None = cont inexistent)
+    pub post_lamports: Vec<Option<u64>>,
+}
+
+impl SimReport {
+    pub fn ok(&self) -> bool {
+        self.err.is_none()
+    }
+
+    /// Ultimele linii relevante din log (erori de program), pentru mesaje scurte.
+    pub fn error_logs(&self, max: usize) -> Vec<&str> {
+        let mut out: Vec<&str> = self
+            .logs
+            .iter()
+            .filter(|l| l.contains("failed") || l.contains("Error") || l.contains("error"))
+            .map(|l| l.as_str())
+            .collect();
+        if out.len() > max {
+            out.drain(..out.len() - max);
+        }
+        out
+    }
+
+    /// CU limit recomandat: consumul măsurat + marjă.
+    pub fn cu_limit(&self) -> Option<u32> {
+        self.units_consumed
+            .filter(|u| *u > 0)
+            .map(|u| (u * (100 + CU_MARGIN_PCT) / 100).min(1_400_000) as u32)
+    }
+}
+
+/// simulateTransaction cu `replaceRecentBlockhash` și `sigVerify: false` (tx-ul poate fi
+/// încă nesemnat sau cu blockhash vechi de la Jupiter). `watch` = conturi ale căror balanțe
+/// post-simulare le vrem înapoi.
+pub async fn simulate(rpcs: &RpcRotator, vtx: &VersionedTransaction, watch: &[Pubkey]) -> Result<SimReport> {
+    let addresses: Vec<String> = watch.iter().map(|p| p.to_string()).collect();
+    let addresses = &addresses;
+
+    let resp = rpcs
+        .call(|c| async move {
+            c.simulate_transaction_with_config(
+                vtx,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: true,
+                    commitment: Some(CommitmentConfig::processed()),
+                    accounts: (!addresses.is_empty()).then(|| RpcSimulateTransactionAccountsConfig {
+                        encoding: None,
+                        addresses: addresses.clone(),
+                    }),
+                    ..Default::default()
+                },
+            )
+            .await
+        })
+        .await?;
+
+    let v = resp.value;
+    let post_lamports = v
+        .accounts
+        .unwrap_or_default()
+        .into_iter()
+        .map(|a| a.map(|acc| acc.lamports))
+        .collect();
+
+    Ok(SimReport {
+        err: v.err,
+        units_consumed: v.units_consumed,
+        logs: v.logs.unwrap_or_default(),
+        post_lamports,
+    })
+}
+
+/// Rescrie instrucțiunile ComputeBudget existente (puse de Jupiter): CU limit = `cu_limit`,
+/// iar prețul per CU se calculează ca tot bugetul `priority_lamports` să se împartă pe CU măsurat.
+/// Întoarce false dacă tx-ul nu are instrucțiuni ComputeBudget. Tx-ul trebuie re-semnat după.
+pub fn apply_compute_budget(vtx: &mut VersionedTransaction, cu_limit: u32, priority_lamports: u64) -> bool {
+    let keys = vtx.message.static_account_keys().to_vec();
+    let cb_id = compute_budget::id();
+    let micro_lamports_per_cu = priority_lamports.saturating_mul(1_000_000) / (cu_limit.max(1) as u64);
+
+    let instructions = match &mut vtx.message {
+        VersionedMessage::Legacy(m) => &mut m.instructions,
+        VersionedMessage::V0(m) => &mut m.instructions,
+    };
+
+    let mut touched = false;
+    for ix in instructions.iter_mut() {
+        if keys.get(ix.program_id_index as usize) != Some(&cb_id) {
+            continue;
+        }
+        // borsh: 2 = SetComputeUnitLimit(u32), 3 = SetComputeUnitPrice(u64)
+        match ix.data.first() {
+            Some(2) => {
+                ix.data = ComputeBudgetInstruction::set_compute_unit_limit(cu_limit).data;
+                touched = true;
+            }
+            Some(3) => {
+                ix.data = ComputeBudgetInstruction::set_compute_unit_price(micro_lamports_per_cu).data;
+                touched = true;
+            }
+            _ => {}
+        }
+    }
+    touched
+}
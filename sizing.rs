@@ -0,0 +1,156 @@
+// src/sizing.rs
+use crate::config::SizingConfig;
+use crate::jupiter::{JupiterClient, QuoteResponse};
+use crate::limiter::Limiter;
+
+/// raportul de aur invers, (sqrt(5) - 1) / 2
+const INV_PHI: f64 = 0.618_033_988_749_895;
+
+/// Un ciclu cotat la o anumită mărime.
+#[derive(Debug, Clone)]
+pub struct CycleEval {
+    pub amount_in: u64,
+    /// câte un quote per picior, în ordinea drumului
+    pub quotes: Vec<QuoteResponse>,
+    /// back(A) - in(A) - costuri, în unitățile raw ale lui A
+    pub net: i128,
+}
+
+/// Caută mărimea care maximizează profitul net al ciclului `path` (A→B→…→A).
+/// Profitul e concav în mărime: mic = costurile fixe domină, mare = impactul domină.
+/// Fiecare evaluare costă `path.len() - 1` quote-uri (prin limiter), în limita `max_quotes`.
+pub async fn optimize(
+    jup: &JupiterClient,
+    limiter: &Limiter,
+    sz: &SizingConfig,
+    path: &[&str],
+    base_amount: u64,
+    costs_raw: u64,
+    max_impact_bps: f64,
+) -> Option<CycleEval> {
+    let mut s = Search {
+        jup,
+        limiter,
+        path,
+        costs_raw,
+        max_impact_bps,
+        quotes_left: sz.max_quotes,
+        best: None,
+        evals: 0,
+    };
+
+    let lo = (base_amount as f64 * sz.min_mult).max(1.0);
+    let hi = (base_amount as f64 * sz.max_mult).max(lo);
+
+    match sz.mode.as_str() {
+        "golden" => s.golden(lo, hi).await,
+        _ => s.ladder(lo, hi, sz.ladder_steps.max(2)).await,
+    }
+
+    if let Some(b) = &s.best {
+        diag!(
+            "[SIZE] {} evals, best in={} (base {}) net={}",
+            s.evals, b.amount_in, base_amount, b.net
+        );
+    }
+    s.best
+}
+
+struct Search<'a> {
+    jup: &'a JupiterClient,
+    limiter: &'a Limiter,
+    path: &'a [&'a str],
+    costs_raw: u64,
+    max_impact_bps: f64,
+    quotes_left: usize,
+    best: Option<CycleEval>,
+    evals: usize,
+}
+
+impl Search<'_> {
+    fn legs(&self) -> usize {
+        self.path.len().saturating_sub(1)
+    }
+
+    fn can_eval(&self) -> bool {
+        self.legs() > 0 && self.quotes_left >= self.legs()
+    }
+
+    /// Net-ul la mărimea `amount`; None = ciclu invalid (out=0, impact peste limită, eroare).
+    /// Pentru căutare, un ciclu invalid contează ca foarte neprofitabil.
+    async fn eval(&mut self, amount: u64) -> Option<i128> {
+        self.quotes_left = self.quotes_left.saturating_sub(self.legs());
+        self.evals += 1;
+
+        let mut quotes = Vec::with_capacity(self.legs());
+        let mut amt = amount;
+        for w in self.path.windows(2) {
+            self.limiter.wait().await;
+            let q = match self.jup.quote(w[0], w[1], amt, Some(false)).await {
+                Ok(q) => q,
+                Err(e) => {
+                    eprintln!("[SIZE] quote {}→{} @{}: {:#}", w[0], w[1], amt, e);
+                    return None;
+                }
+            };
+            if q.out_amount == 0 || q.price_impact_bps() > self.max_impact_bps {
+                return None;
+            }
+            amt = q.out_amount;
+            quotes.push(q);
+        }
+
+        let net = amt as i128 - amount as i128 - self.costs_raw as i128;
+        if self.best.as_ref().map(|b| net > b.net).unwrap_or(true) {
+            self.best = Some(CycleEval { amount_in: amount, quotes, net });
+        }
+        Some(net)
+    }
+
+    async fn score(&mut self, amount: f64) -> i128 {
+        self.eval(amount.round() as u64).await.unwrap_or(i128::MIN / 2)
+    }
+
+    /// Scară geometrică lo..hi, în ordine crescătoare.
+    async fn ladder(&mut self, lo: f64, hi: f64, steps: usize) {
+        let ratio = hi / lo;
+        for i in 0..steps {
+            if !self.can_eval() {
+                break;
+            }
+            let x = lo * ratio.powf(i as f64 / (steps - 1) as f64);
+            self.score(x).await;
+        }
+    }
+
+    /// Golden-section pe ln(mărime), cât ține bugetul de quote-uri.
+    async fn golden(&mut self, lo: f64, hi: f64) {
+        let (mut a, mut b) = (lo.ln(), hi.ln());
+        if !self.can_eval() {
+            return;
+        }
+        let mut c = b - INV_PHI * (b - a);
+        let mut fc = self.score(c.exp()).await;
+        if !self.can_eval() {
+            return;
+        }
+        let mut d = a + INV_PHI * (b - a);
+        let mut fd = self.score(d.exp()).await;
+
+        while self.can_eval() && (b - a) > 1e-3 {
+            if fc >= fd {
+                b = d;
+                d = c;
+                fd = fc;
+                c = b - INV_PHI * (b - a);
+                fc = self.score(c.exp()).await;
+            } else {
+                a = c;
+                c = d;
+                fc = fd;
+                d = a + INV_PHI * (b - a);
+                fd = self.score(d.exp()).await;
+            }
+        }
+    }
+}
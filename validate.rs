@@ -0,0 +1,422 @@
+// src/validate.rs
+use serde::Serialize;
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use std::fmt;
+
+use crate::config::Config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// O problemă în config, cu calea JSON (ex. `$.pairs[2].inputMint`).
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    pub path: String,
+    pub msg: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tag = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}: {}", tag, self.path, self.msg)
+    }
+}
+
+/// Config invalid: toate erorile găsite, nu doar prima.
+#[derive(Debug, thiserror::Error)]
+#[error("invalid config ({} error(s)):\n{}", .0.len(), render(.0))]
+pub struct ConfigError(pub Vec<Issue>);
+
+fn render(issues: &[Issue]) -> String {
+    issues.iter().map(|i| format!("  - {}", i)).collect::<Vec<_>>().join("\n")
+}
+
+#[derive(Default)]
+pub struct Report {
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    fn err(&mut self, path: impl Into<String>, msg: impl Into<String>) {
+        self.issues.push(Issue { severity: Severity::Error, path: path.into(), msg: msg.into() });
+    }
+
+    fn warn(&mut self, path: impl Into<String>, msg: impl Into<String>) {
+        self.issues.push(Issue { severity: Severity::Warning, path: path.into(), msg: msg.into() });
+    }
+
+    pub fn errors(&self) -> Vec<Issue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Error).cloned().collect()
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Issue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Warning)
+    }
+
+    fn pubkey(&mut self, path: impl Into<String>, s: &str) {
+        if let Err(e) = s.parse::<Pubkey>() {
+            self.err(path, format!("invalid pubkey {:?}: {}", s, e));
+        }
+    }
+
+    fn bps(&mut self, path: impl Into<String>, v: f64) {
+        if !v.is_finite() || v < 0.0 {
+            self.err(path, format!("{} must be >= 0 bps", v));
+        }
+    }
+
+    fn url(&mut self, path: impl Into<String>, s: &str, schemes: &[&str]) {
+        if !schemes.iter().any(|p| s.starts_with(&format!("{}://", p))) {
+            self.err(path, format!("{:?} is not a {} URL", s, schemes.join("/")));
+        }
+    }
+}
+
+/// Aliasuri acceptate la deserializare -> numele canonic (cel scris la serializare).
+const KEY_ALIASES: &[(&str, &str)] = &[
+    ("name", "label"),
+    ("tri_leg", "three_leg"),
+    ("use_", "use"),
+    ("path", "legs"),
+];
+
+/// Validează config-ul parsat. `raw` e JSON-ul original, folosit pentru chei necunoscute.
+pub fn validate(cfg: &Config, raw: &Value) -> Report {
+    let mut r = Report::default();
+
+    check_unknown_keys(cfg, raw, &mut r);
+
+    if let Some(p) = &cfg.wallet_keypair_path {
+        if p.trim().is_empty() {
+            r.err("$.wallet_keypair_path", "empty path");
+        }
+    }
+
+    // Jupiter
+    r.url("$.jupiter_base", &cfg.jupiter_base, &["http", "https"]);
+    if cfg.max_slippage_bps == 0 || cfg.max_slippage_bps > 10_000 {
+        r.err("$.max_slippage_bps", format!("{} not in 1..=10000", cfg.max_slippage_bps));
+    }
+    if cfg.min_profit_bps > 10_000 {
+        r.err("$.min_profit_bps", format!("{} > 10000", cfg.min_profit_bps));
+    }
+    if let Some(mp) = &cfg.min_profit {
+        match mp.mode.as_str() {
+            "abs" => {
+                if mp.denom.as_deref().unwrap_or("SOL") != "SOL" {
+                    r.err("$.min_profit.denom", format!("{:?} not supported (only \"SOL\")", mp.denom));
+                }
+            }
+            "pct" => {
+                if mp.value > 1.0 {
+                    r.err("$.min_profit.value", "pct mode expects a fraction (0.001 = 0.1%)");
+                }
+            }
+            other => r.err("$.min_profit.mode", format!("unknown mode {:?} (expected \"abs\" | \"pct\")", other)),
+        }
+        if !mp.value.is_finite() || mp.value < 0.0 {
+            r.err("$.min_profit.value", format!("{} must be >= 0", mp.value));
+        }
+    }
+    r.url("$.resolver.url", &cfg.resolver.url, &["http", "https"]);
+    if cfg.resolver.snapshot_path.trim().is_empty() {
+        r.err("$.resolver.snapshot_path", "empty path");
+    }
+    if cfg.quote_cache.ttl_ms > 60_000 {
+        r.warn("$.quote_cache.ttl_ms", format!("{}ms is very long for quotes", cfg.quote_cache.ttl_ms));
+    }
+
+    // exec
+    if !cfg.notional_sol.is_finite() || cfg.notional_sol <= 0.0 {
+        r.err("$.notional_sol", format!("{} must be > 0", cfg.notional_sol));
+    }
+    if cfg.poll_ms == 0 {
+        r.err("$.poll_ms", "must be > 0");
+    }
+
+    // perechi
+    if cfg.pairs.is_empty() {
+        r.err("$.pairs", "no pairs configured");
+    }
+    for (i, p) in cfg.pairs.iter().enumerate() {
+        r.pubkey(format!("$.pairs[{i}].inputMint"), &p.input_mint);
+        r.pubkey(format!("$.pairs[{i}].outputMint"), &p.output_mint);
+        if p.input_mint == p.output_mint {
+            r.err(format!("$.pairs[{i}]"), "inputMint == outputMint");
+        }
+        if let Some(n) = p.notional {
+            if !n.is_finite() || n <= 0.0 {
+                r.err(format!("$.pairs[{i}].notional"), format!("{} must be > 0", n));
+            }
+        }
+        if let Some(b) = p.min_profit_bps {
+            if b > 10_000 {
+                r.err(format!("$.pairs[{i}].min_profit_bps"), format!("{} > 10000", b));
+            }
+        }
+        if let Some(b) = p.max_price_impact_bps {
+            r.bps(format!("$.pairs[{i}].max_price_impact_bps"), b);
+        }
+        if let Some(b) = p.slippage_bps {
+            if b == 0 || b > 10_000 {
+                r.err(format!("$.pairs[{i}].slippage_bps"), format!("{} not in 1..=10000", b));
+            }
+        }
+    }
+
+    // risk (bps)
+    r.bps("$.risk.pair_max_impact_bps", cfg.risk.pair_max_impact_bps);
+    r.bps("$.risk.unwind_max_impact_bps", cfg.risk.unwind_max_impact_bps);
+    if let Some(b) = cfg.risk.two_leg_max_impact_bps {
+        r.bps("$.risk.two_leg_max_impact_bps", b);
+    }
+    if let Some(b) = cfg.risk.three_leg_max_impact_bps {
+        r.bps("$.risk.three_leg_max_impact_bps", b);
+    }
+    for (path, v) in [
+        ("$.risk.pair_max_impact_bps", cfg.risk.pair_max_impact_bps),
+        ("$.risk.unwind_max_impact_bps", cfg.risk.unwind_max_impact_bps),
+    ] {
+        if v > 0.0 && v < 1.0 {
+            r.warn(path, format!("{} bps = {}% (limits are in bps, not fractions)", v, v / 100.0));
+        }
+    }
+
+    // bundles
+    if let Some(b) = &cfg.bundles {
+        for (i, bl) in b.two_leg.iter().enumerate() {
+            r.pubkey(format!("$.bundles.two_leg[{i}].from"), &bl.from);
+            r.pubkey(format!("$.bundles.two_leg[{i}].to"), &bl.to);
+            if bl.from == bl.to {
+                r.err(format!("$.bundles.two_leg[{i}]"), "from == to");
+            }
+            if let Some(b) = bl.max_price_impact_bps {
+                r.bps(format!("$.bundles.two_leg[{i}].max_price_impact_bps"), b);
+            }
+        }
+        for (i, bl) in b.tri_leg.iter().enumerate() {
+            let path = format!("$.bundles.three_leg[{i}].legs");
+            if bl.legs.len() != 4 {
+                r.err(&path, format!("expected 4 mints A→B→C→A, got {}", bl.legs.len()));
+            } else if bl.legs[0] != bl.legs[3] {
+                r.err(&path, "cycle must end in its first mint");
+            }
+            for (j, m) in bl.legs.iter().enumerate() {
+                r.pubkey(format!("{path}[{j}]"), m);
+            }
+            if let Some(b) = bl.max_price_impact_bps {
+                r.bps(format!("$.bundles.three_leg[{i}].max_price_impact_bps"), b);
+            }
+        }
+        let e = &b.execution;
+        r.bps("$.bundles.execution.price_impact_bps_limit", e.price_impact_bps_limit);
+        if e.timeout_ms == 0 {
+            r.err("$.bundles.execution.timeout_ms", "must be > 0");
+        }
+        if let Some(ts) = &b.tri_scan {
+            if ts.enabled && ts.tokens.len() < 3 {
+                r.err("$.bundles.tri_scan.tokens", "need at least 3 symbols (base first)");
+            }
+        }
+        if let Some(sz) = &b.sizing {
+            if !matches!(sz.mode.as_str(), "ladder" | "golden") {
+                r.err("$.bundles.sizing.mode", format!("unknown mode {:?} (ladder|golden)", sz.mode));
+            }
+            if !sz.min_mult.is_finite() || sz.min_mult <= 0.0 || !sz.max_mult.is_finite() || sz.max_mult < sz.min_mult {
+                r.err("$.bundles.sizing", "need 0 < min_mult <= max_mult");
+            }
+            if sz.mode == "ladder" && sz.ladder_steps < 2 {
+                r.err("$.bundles.sizing.ladder_steps", "must be >= 2");
+            }
+            if sz.enabled && sz.max_quotes < 6 {
+                r.warn("$.bundles.sizing.max_quotes", "budget below 2 three-leg evaluations; search is mostly a no-op");
+            }
+        }
+    }
+
+    // tokens
+    if let Some(tokens) = &cfg.tokens {
+        for (sym, v) in tokens {
+            match v.as_str() {
+                Some(m) => r.pubkey(format!("$.tokens.{sym}"), m),
+                None => r.err(format!("$.tokens.{sym}"), "expected a mint string"),
+            }
+        }
+    }
+
+    // RPC
+    if cfg.rpcs.is_empty() {
+        r.err("$.rpcs", "no RPC endpoints");
+    }
+    for (i, u) in cfg.rpcs.iter().enumerate() {
+        r.url(format!("$.rpcs[{i}]"), u, &["http", "https"]);
+    }
+    for (i, u) in cfg.ws_rpcs.iter().flatten().enumerate() {
+        r.url(format!("$.ws_rpcs[{i}]"), u, &["ws", "wss"]);
+    }
+    let rc = &cfg.rpc_config;
+    if !matches!(rc.commitment.as_str(), "processed" | "confirmed" | "finalized") {
+        r.err("$.rpc_config.commitment", format!("unknown commitment {:?}", rc.commitment));
+    }
+    if rc.timeout_ms == 0 {
+        r.err("$.rpc_config.timeout_ms", "must be > 0");
+    }
+
+    // jito
+    if cfg.jito.use_ {
+        r.url("$.jito.block_engine", &cfg.jito.block_engine, &["http", "https"]);
+        r.pubkey("$.jito.tip_account", &cfg.jito.tip_account);
+        if cfg.jito.default_tip_lamports < 1_000 {
+            r.warn("$.jito.default_tip_lamports", "below the 1000 lamports Jito minimum; bundles may be dropped");
+        }
+    }
+
+    // limiter
+    if cfg.limiter.rps == 0 {
+        r.err("$.limiter.rps", "must be > 0");
+    }
+    if cfg.limiter.burst == 0 {
+        r.err("$.limiter.burst", "must be > 0");
+    }
+
+    // account_setup
+    if let Some(s) = &cfg.account_setup {
+        for (i, m) in s.mints.iter().enumerate() {
+            r.pubkey(format!("$.account_setup.mints[{i}]"), m);
+        }
+        if s.create_if_missing && !s.ensure_atas {
+            r.warn("$.account_setup.create_if_missing", "has no effect while ensure_atas=false");
+        }
+    }
+
+    // cleanup
+    if let Some(c) = &cfg.cleanup {
+        if c.every_ms < 60_000 {
+            r.err("$.cleanup.every_ms", "must be >= 60000");
+        }
+        if c.sweep_min_lamports > c.dust_max_lamports {
+            r.warn("$.cleanup.sweep_min_lamports", "above dust_max_lamports; dust is always burned, never swept");
+        }
+    }
+
+    // logs
+    if let Some(l) = cfg.logs.as_ref().filter(|l| l.json_events) {
+        if !matches!(l.rotate.as_str(), "daily" | "hourly") {
+            r.err("$.logs.rotate", format!("unknown value {:?} (expected \"daily\" | \"hourly\")", l.rotate));
+        }
+        if l.dir.trim().is_empty() {
+            r.err("$.logs.dir", "must not be empty");
+        }
+        if l.keep_files == 1 {
+            r.warn("$.logs.keep_files", "1 keeps only the current file; the previous period is deleted at rotation");
+        }
+    }
+
+    // metrics
+    if let Some(m) = cfg.metrics.as_ref().filter(|m| m.enabled) {
+        match m.listen.parse::<std::net::SocketAddr>() {
+            Ok(a) if a.ip().is_unspecified() => {
+                r.warn("$.metrics.listen", format!("{} exposes unauthenticated metrics on every interface", a));
+            }
+            Ok(_) => {}
+            Err(e) => r.err("$.metrics.listen", format!("{:?} is not ip:port ({})", m.listen, e)),
+        }
+        if m.balances_every_ms > 0 && m.balances_every_ms < 5_000 {
+            r.warn("$.metrics.balances_every_ms", "below 5000 spends RPC quota on balance polling");
+        }
+    }
+
+    // auto-unwind
+    if let Some(au) = &cfg.auto_unwind {
+        r.pubkey("$.auto_unwind.base_mint", &au.base_mint);
+        // cât timp e oprit, un mode necunoscut nu blochează pornirea
+        if !matches!(au.mode.as_str(), "always" | "pnl_gt_0" | "bps") {
+            let msg = format!("unknown mode {:?} (expected \"always\" | \"pnl_gt_0\" | \"bps\")", au.mode);
+            if au.enabled {
+                r.err("$.auto_unwind.mode", msg);
+            } else {
+                r.warn("$.auto_unwind.mode", msg);
+            }
+        }
+        if !au.min_token_ui.is_finite() || au.min_token_ui < 0.0 {
+            r.err("$.auto_unwind.min_token_ui", "must be >= 0");
+        }
+        if au.check_every_ms == 0 {
+            r.err("$.auto_unwind.check_every_ms", "must be > 0");
+        }
+        if au.mode == "bps" && au.min_profit_bps == 0 {
+            r.warn("$.auto_unwind.min_profit_bps", "0 with mode \"bps\" behaves like \"pnl_gt_0\"");
+        }
+        if !(au.sell_fraction > 0.0 && au.sell_fraction <= 1.0) {
+            r.err("$.auto_unwind.sell_fraction", format!("{} not in (0, 1]", au.sell_fraction));
+        }
+        for (k, t) in &au.per_token {
+            let path = format!("$.auto_unwind.per_token.{k}");
+            if let Some(f) = t.sell_fraction {
+                if !(f > 0.0 && f <= 1.0) {
+                    r.err(format!("{path}.sell_fraction"), format!("{} not in (0, 1]", f));
+                }
+            }
+            for (field, v) in [("min_ui", t.min_ui), ("reserve_ui", t.reserve_ui)] {
+                if let Some(v) = v {
+                    if !v.is_finite() || v < 0.0 {
+                        r.err(format!("{path}.{field}"), "must be >= 0");
+                    }
+                }
+            }
+        }
+        for t in au.tokens.iter().filter(|t| au.deny.contains(t)) {
+            r.warn("$.auto_unwind.deny", format!("{} is in both tokens and deny; deny wins", t));
+        }
+        if au.max_hold_ms == Some(0) {
+            r.err("$.auto_unwind.max_hold_ms", "must be > 0 (omit it to never force a sale)");
+        }
+    }
+
+    r
+}
+
+/// Chei din JSON pe care `Config` nu le citește (typo-uri, secțiuni mutate).
+/// Comparăm JSON-ul original cu config-ul re-serializat.
+fn check_unknown_keys(cfg: &Config, raw: &Value, r: &mut Report) {
+    // caz frecvent: "execution" pus la rădăcină în loc de "bundles.execution"
+    if raw.get("execution").is_some() {
+        r.err("$.execution", "must live under \"bundles\" (bundles.execution); at top level it is ignored");
+    }
+
+    match serde_json::to_value(cfg) {
+        Ok(known) => diff_keys(raw, &known, "$", r),
+        Err(e) => r.warn("$", format!("cannot check unknown keys: {}", e)),
+    }
+}
+
+fn diff_keys(raw: &Value, known: &Value, path: &str, r: &mut Report) {
+    match (raw, known) {
+        (Value::Object(ro), Value::Object(ko)) => {
+            for (k, rv) in ro {
+                let canon = KEY_ALIASES.iter().find(|(a, _)| a == k).map(|(_, c)| *c).unwrap_or(k);
+                let child = format!("{path}.{k}");
+                match ko.get(k).or_else(|| ko.get(canon)) {
+                    Some(kv) => diff_keys(rv, kv, &child, r),
+                    None if path == "$" && k == "execution" => {} // raportat deja ca eroare
+                    None => r.warn(child, "unknown key (ignored)"),
+                }
+            }
+        }
+        (Value::Array(ra), Value::Array(ka)) => {
+            for (i, (rv, kv)) in ra.iter().zip(ka.iter()).enumerate() {
+                diff_keys(rv, kv, &format!("{path}[{i}]"), r);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,66 @@
+// src/window.rs
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Ultimele `cap` valori, pentru percentile în rezumatul orar (latențe, PnL estimat).
+#[derive(Debug, Clone)]
+pub struct Window {
+    vals: VecDeque<f64>,
+    cap: usize,
+    /// toate valorile văzute, nu doar cele păstrate
+    count: u64,
+    sum: f64,
+}
+
+/// Distribuția valorilor din fereastră; `count`/`sum` sunt pe toată durata rulării.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Summary {
+    pub count: u64,
+    pub sum: f64,
+    pub min: f64,
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl Window {
+    pub fn new(cap: usize) -> Self {
+        Self {
+            vals: VecDeque::with_capacity(cap.min(1024)),
+            cap: cap.max(1),
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    pub fn push(&mut self, v: f64) {
+        if self.vals.len() == self.cap {
+            self.vals.pop_front();
+        }
+        self.vals.push_back(v);
+        self.count += 1;
+        self.sum += v;
+    }
+
+    /// None până la prima valoare. Percentilele sunt nearest-rank pe fereastră.
+    pub fn summary(&self) -> Option<Summary> {
+        if self.vals.is_empty() {
+            return None;
+        }
+        let mut v: Vec<f64> = self.vals.iter().copied().collect();
+        v.sort_by(f64::total_cmp);
+        let at = |p: f64| v[((p * v.len() as f64).ceil() as usize).clamp(1, v.len()) - 1];
+        Some(Summary {
+            count: self.count,
+            sum: self.sum,
+            min: v[0],
+            p10: at(0.10),
+            p50: at(0.50),
+            p90: at(0.90),
+            p99: at(0.99),
+            max: v[v.len() - 1],
+        })
+    }
+}